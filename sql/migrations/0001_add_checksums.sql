@@ -0,0 +1,4 @@
+--
+-- Add column checksums to JVM
+--
+ALTER TABLE JVM ADD COLUMN IF NOT EXISTS checksums TEXT;
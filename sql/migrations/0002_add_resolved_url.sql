@@ -0,0 +1,4 @@
+--
+-- Add column resolved_url to JVM
+--
+ALTER TABLE JVM ADD COLUMN IF NOT EXISTS resolved_url TEXT;
@@ -0,0 +1,15 @@
+--
+-- Create Table VENDOR_RUNS
+--
+CREATE TABLE IF NOT EXISTS VENDOR_RUNS (
+    vendor TEXT NOT NULL,
+    fetched_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    duration_ms BIGINT NOT NULL,
+    fetched BIGINT NOT NULL,
+    modified BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    PRIMARY KEY(vendor)
+);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON VENDOR_RUNS TO roast;
@@ -0,0 +1,4 @@
+--
+-- Widen column size of JVM to store files larger than 2 GiB
+--
+ALTER TABLE JVM ALTER COLUMN "size" TYPE BIGINT;
@@ -0,0 +1,10 @@
+--
+-- Create Table SCHEMA_MIGRATIONS recording the migrations applied by `roast migrate`
+--
+CREATE TABLE IF NOT EXISTS SCHEMA_MIGRATIONS (
+    version INTEGER NOT NULL,
+    applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(version)
+);
+
+GRANT SELECT ON SCHEMA_MIGRATIONS TO roast;
@@ -0,0 +1,4 @@
+--
+-- Add column build to JVM
+--
+ALTER TABLE JVM ADD COLUMN IF NOT EXISTS build TEXT;
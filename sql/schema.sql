@@ -4,8 +4,10 @@
 DROP TABLE IF EXISTS JVM;
 CREATE TABLE JVM (
     architecture TEXT NOT NULL,
+    build TEXT,
     "checksum" TEXT,
     checksum_url TEXT,
+    checksums TEXT,
     created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
     features TEXT,
     file_type TEXT NOT NULL,
@@ -16,7 +18,8 @@ CREATE TABLE JVM (
     modified_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
     os TEXT NOT NULL,
     release_type TEXT NOT NULL,
-    "size" INTEGER,
+    resolved_url TEXT,
+    "size" BIGINT,
     "url" TEXT NOT NULL,
     vendor TEXT NOT NULL,
     "version" TEXT NOT NULL,
@@ -40,6 +43,34 @@ DROP INDEX IF EXISTS JVM_IDX_VERSION;
 CREATE INDEX JVM_IDX_VERSION ON JVM ("version");
 
 --
--- Allow read/write for user roast
+-- Create Table VENDOR_RUNS
+--
+DROP TABLE IF EXISTS VENDOR_RUNS;
+CREATE TABLE VENDOR_RUNS (
+    vendor TEXT NOT NULL,
+    fetched_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    duration_ms BIGINT NOT NULL,
+    fetched BIGINT NOT NULL,
+    modified BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    error TEXT,
+    PRIMARY KEY(vendor)
+);
+
+--
+-- Create Table SCHEMA_MIGRATIONS, the schema includes all migrations of ./sql/migrations
+--
+DROP TABLE IF EXISTS SCHEMA_MIGRATIONS;
+CREATE TABLE SCHEMA_MIGRATIONS (
+    version INTEGER NOT NULL,
+    applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(version)
+);
+INSERT INTO SCHEMA_MIGRATIONS (version) VALUES (1), (2), (3), (4), (5), (6);
+
+--
+-- Allow read/write for user roast, migrations are applied by the owner of the tables so roast only reads their version
 --
 GRANT SELECT, INSERT, UPDATE, DELETE ON JVM TO roast;
+GRANT SELECT, INSERT, UPDATE, DELETE ON VENDOR_RUNS TO roast;
+GRANT SELECT ON SCHEMA_MIGRATIONS TO roast;
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use eyre::Result;
+use log::warn;
+
+use crate::db::{jvm_repository::JvmRepository, pool::ConnectionPool};
+
+/// Report GA records without a checksum
+///
+/// Lists the URLs of GA records without any checksum grouped by vendor and fails if any are found
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Checksums {
+    /// Only warn about missing checksums instead of failing
+    #[clap(long, default_value = "false")]
+    pub warn_only: bool,
+}
+
+impl Checksums {
+    pub fn run(self) -> Result<()> {
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
+
+        let missing = group_by_vendor(db.get_missing_checksums("ga")?);
+        if missing.is_empty() {
+            println!("all GA records have a checksum");
+            return Ok(());
+        }
+
+        let mut total = 0;
+        for (vendor, urls) in &missing {
+            println!("{vendor}: {} records without checksum", urls.len());
+            for url in urls {
+                println!("  {url}");
+            }
+            total += urls.len();
+        }
+
+        let message = format!("{total} GA records of {} vendors without checksum", missing.len());
+        if self.warn_only {
+            warn!("{message}");
+            Ok(())
+        } else {
+            Err(eyre::eyre!(message))
+        }
+    }
+}
+
+/// Groups the URLs of `(vendor, url)` pairs by vendor
+fn group_by_vendor(records: Vec<(String, String)>) -> BTreeMap<String, Vec<String>> {
+    let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (vendor, url) in records {
+        map.entry(vendor).or_default().push(url);
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_vendor() {
+        let records = [
+            ("zulu", "https://example.com/zulu-21.tar.gz"),
+            ("oracle", "https://example.com/jdk-21.tar.gz"),
+            ("zulu", "https://example.com/zulu-17.tar.gz"),
+        ]
+        .map(|(vendor, url)| (vendor.to_string(), url.to_string()))
+        .to_vec();
+
+        let actual = group_by_vendor(records);
+        assert_eq!(actual.keys().collect::<Vec<_>>(), vec!["oracle", "zulu"]);
+        assert_eq!(
+            actual["zulu"],
+            vec![
+                "https://example.com/zulu-21.tar.gz",
+                "https://example.com/zulu-17.tar.gz"
+            ]
+        );
+        assert!(group_by_vendor(vec![]).is_empty());
+    }
+}
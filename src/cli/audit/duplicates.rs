@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use eyre::Result;
+use log::warn;
+
+use crate::db::{jvm_repository::JvmRepository, pool::ConnectionPool};
+
+/// Report artifacts provided by more than one vendor
+///
+/// Lists records of different vendors sharing the same checksum or the same download target (the resolved_url of
+/// redirected records) to help deciding which vendor should provide an artifact
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Duplicates {
+    /// Property identifying an artifact e.g.: checksum, url
+    #[clap(long, default_value = "checksum", value_parser = ["checksum", "url"])]
+    pub by: String,
+    /// Fail if any duplicates are found
+    #[clap(long, default_value = "false")]
+    pub fail: bool,
+}
+
+impl Duplicates {
+    pub fn run(self) -> Result<()> {
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
+
+        let duplicates = group_duplicates(db.get_cross_vendor_duplicates(&self.by)?);
+        if duplicates.is_empty() {
+            println!("no records with the same {} across vendors", self.by);
+            return Ok(());
+        }
+
+        for (key, records) in &duplicates {
+            let vendors = records
+                .iter()
+                .map(|(vendor, _)| vendor.as_str())
+                .collect::<BTreeSet<_>>();
+            println!("{key}: {}", vendors.into_iter().collect::<Vec<_>>().join(", "));
+            for (vendor, url) in records {
+                println!("  {vendor}: {url}");
+            }
+        }
+
+        let message = format!("{} artifacts provided by more than one vendor", duplicates.len());
+        if self.fail {
+            Err(eyre::eyre!(message))
+        } else {
+            warn!("{message}");
+            Ok(())
+        }
+    }
+}
+
+/// Groups `(key, vendor, url)` rows by key, keeping only keys shared by more than one vendor
+fn group_duplicates(rows: Vec<(String, String, String)>) -> BTreeMap<String, Vec<(String, String)>> {
+    let mut map: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for (key, vendor, url) in rows {
+        map.entry(key).or_default().push((vendor, url));
+    }
+    map.retain(|_, records| records.iter().map(|(vendor, _)| vendor).collect::<BTreeSet<_>>().len() > 1);
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_duplicates() {
+        let rows = [
+            (
+                "https://example.com/openjdk-21.tar.gz",
+                "openjdk",
+                "https://example.com/openjdk-21.tar.gz",
+            ),
+            (
+                "https://example.com/openjdk-21.tar.gz",
+                "microsoft",
+                "https://mirror.example.com/openjdk-21.tar.gz",
+            ),
+            (
+                "https://example.com/zulu-21.tar.gz",
+                "zulu",
+                "https://example.com/zulu-21.tar.gz",
+            ),
+            (
+                "https://example.com/zulu-21.tar.gz",
+                "zulu",
+                "https://mirror.example.com/zulu-21.tar.gz",
+            ),
+        ]
+        .map(|(key, vendor, url)| (key.to_string(), vendor.to_string(), url.to_string()))
+        .to_vec();
+
+        let actual = group_duplicates(rows);
+        assert_eq!(
+            actual.keys().collect::<Vec<_>>(),
+            vec!["https://example.com/openjdk-21.tar.gz"]
+        );
+        assert_eq!(
+            actual["https://example.com/openjdk-21.tar.gz"],
+            vec![
+                (
+                    "openjdk".to_string(),
+                    "https://example.com/openjdk-21.tar.gz".to_string()
+                ),
+                (
+                    "microsoft".to_string(),
+                    "https://mirror.example.com/openjdk-21.tar.gz".to_string()
+                ),
+            ]
+        );
+        assert!(group_duplicates(vec![]).is_empty());
+    }
+}
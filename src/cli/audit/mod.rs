@@ -0,0 +1,32 @@
+use clap::Subcommand;
+
+mod checksums;
+mod duplicates;
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Checksums(checksums::Checksums),
+    Duplicates(duplicates::Duplicates),
+}
+
+impl Commands {
+    pub fn run(self) -> eyre::Result<()> {
+        match self {
+            Self::Checksums(cmd) => cmd.run(),
+            Self::Duplicates(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// Audit JVM data
+#[derive(Debug, clap::Args)]
+pub struct Audit {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+impl Audit {
+    pub fn run(self) -> eyre::Result<()> {
+        self.command.run()
+    }
+}
@@ -0,0 +1,46 @@
+use std::io::Write;
+
+use clap_complete::{Shell, generate};
+use eyre::Result;
+
+use super::Cli;
+
+/// Generate shell completions
+///
+/// Prints the completion script for the given shell to stdout e.g.:
+///   roast completions bash > /etc/bash_completion.d/roast
+///   roast completions zsh > ~/.zfunc/_roast
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Completions {
+    /// Shell to generate completions for
+    #[clap(value_enum)]
+    pub shell: Shell,
+}
+
+impl Completions {
+    pub fn run(self) -> Result<()> {
+        write_completions(self.shell, &mut std::io::stdout())
+    }
+}
+
+fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut cmd = Cli::command();
+    generate(shell, &mut cmd, "roast", out);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_completions() {
+        for shell in [Shell::Bash, Shell::Elvish, Shell::Fish, Shell::PowerShell, Shell::Zsh] {
+            let mut out = Vec::new();
+            write_completions(shell, &mut out).unwrap();
+            let script = String::from_utf8(out).unwrap();
+            assert!(script.contains("roast"), "{shell} completions should reference roast");
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use eyre::Result;
+
+use crate::db::{
+    maintenance_repository::MaintenanceRepository,
+    migration_repository::{MIGRATIONS, MigrationRepository},
+    pool::ConnectionPool,
+};
+
+/// Check the integrity of the database
+///
+/// Reports missing tables, invalid indexes e.g. of an interrupted index build and a schema older than the latest
+/// migration, fails if any are found
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Check {}
+
+impl Check {
+    pub fn run(self) -> Result<()> {
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = MaintenanceRepository::new(conn_pool.clone())?;
+        let migrations = MigrationRepository::new(conn_pool)?;
+        let problems = find_problems(
+            &db.get_missing_tables()?,
+            &db.get_invalid_indexes()?,
+            migrations.get_version()?,
+        );
+        if problems.is_empty() {
+            println!("database is ok");
+            return Ok(());
+        }
+        for problem in &problems {
+            println!("{problem}");
+        }
+        Err(eyre::eyre!("{} database problems found", problems.len()))
+    }
+}
+
+/// Returns the problems of the database, none if it is ok
+fn find_problems(missing_tables: &[String], invalid_indexes: &[String], version: u32) -> Vec<String> {
+    let latest = MIGRATIONS
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default();
+    let mut problems = Vec::new();
+    problems.extend(missing_tables.iter().map(|table| format!("table {table} is missing")));
+    problems.extend(
+        invalid_indexes
+            .iter()
+            .map(|index| format!("index {index} is invalid, rebuild it with REINDEX INDEX {index}")),
+    );
+    if version < latest {
+        problems.push(format!(
+            "schema version {version} is older than {latest}, run roast migrate"
+        ));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::maintenance_repository::TABLES;
+
+    use super::*;
+
+    #[test]
+    fn test_find_problems() {
+        // a database freshly built from the schema has all tables and the latest version
+        let schema = include_str!("../../../sql/schema.sql").to_lowercase();
+        let missing = TABLES
+            .iter()
+            .filter(|table| !schema.contains(&format!("create table {table} (")))
+            .map(|table| table.to_string())
+            .collect::<Vec<_>>();
+        let latest = MIGRATIONS.len() as u32;
+        assert!(find_problems(&missing, &[], latest).is_empty(), "{missing:?}");
+
+        let problems = find_problems(&["vendor_runs".to_string()], &["jvm_idx_os".to_string()], latest - 1);
+        assert_eq!(
+            problems,
+            vec![
+                "table vendor_runs is missing".to_string(),
+                "index jvm_idx_os is invalid, rebuild it with REINDEX INDEX jvm_idx_os".to_string(),
+                format!(
+                    "schema version {} is older than {latest}, run roast migrate",
+                    latest - 1
+                ),
+            ]
+        );
+    }
+}
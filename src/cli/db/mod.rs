@@ -0,0 +1,32 @@
+use clap::Subcommand;
+
+mod check;
+mod vacuum;
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Check(check::Check),
+    Vacuum(vacuum::Vacuum),
+}
+
+impl Commands {
+    pub fn run(self) -> eyre::Result<()> {
+        match self {
+            Self::Check(cmd) => cmd.run(),
+            Self::Vacuum(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// Maintain the database
+#[derive(Debug, clap::Args)]
+pub struct Db {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+impl Db {
+    pub fn run(self) -> eyre::Result<()> {
+        self.command.run()
+    }
+}
@@ -0,0 +1,42 @@
+use eyre::Result;
+use log::info;
+
+use crate::db::{
+    maintenance_repository::{MaintenanceRepository, TABLES},
+    pool::ConnectionPool,
+};
+
+/// Vacuum and analyze the tables
+///
+/// Marks the space of deleted and updated records as reusable and refreshes the statistics of the query planner, a full
+/// vacuum rewrites the tables and returns the space to the OS but locks them meanwhile e.g.:
+///   roast db vacuum
+///   roast db vacuum --full
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Vacuum {
+    /// Rewrite the tables to return the reclaimed space to the OS, the tables are locked until done
+    #[clap(long, default_value = "false")]
+    pub full: bool,
+}
+
+impl Vacuum {
+    pub fn run(self) -> Result<()> {
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = MaintenanceRepository::new(conn_pool)?;
+        for table in TABLES {
+            let Some(before) = db.get_size(table)? else {
+                info!("skipping missing table {table}");
+                continue;
+            };
+            info!("vacuuming {table}");
+            db.vacuum(table, self.full)?;
+            let after = db.get_size(table)?.unwrap_or_default();
+            println!(
+                "{table}: {before} -> {after} bytes, reclaimed {}",
+                (before - after).max(0)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+use serde_json::{Map, Value};
+
+/// Records of an export tree keyed by URL
+type Records = BTreeMap<String, Map<String, Value>>;
+
+/// Compare two export directories
+///
+/// Reports the number of added, removed and changed records of each exported file which differs, records are
+/// matched by their URL e.g.:
+///   roast diff old/public/api/jvm public/api/jvm
+///   roast diff --summary-by vendor old/public/api/jvm public/api/jvm
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Diff {
+    /// Directory of the previous export
+    #[clap(value_name = "OLD")]
+    pub old: PathBuf,
+    /// Directory of the current export
+    #[clap(value_name = "NEW")]
+    pub new: PathBuf,
+    /// Summarize the changes by the given property instead of by file, unchanged values are skipped
+    #[clap(long, value_name = "PROPERTY", value_parser = ["vendor"])]
+    pub summary_by: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Changes {
+    added: usize,
+    removed: usize,
+    changed: usize,
+}
+
+impl Changes {
+    fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.changed == 0
+    }
+}
+
+impl Diff {
+    pub fn run(self) -> Result<()> {
+        let old = read_tree(&self.old)?;
+        let new = read_tree(&self.new)?;
+
+        let changes = match self.summary_by {
+            Some(_) => diff_by_vendor(&old, &new),
+            None => diff_by_file(&old, &new),
+        };
+        if changes.is_empty() {
+            println!("no changes");
+            return Ok(());
+        }
+        for (name, changes) in &changes {
+            println!(
+                "{name}: {} added, {} removed, {} changed",
+                changes.added, changes.removed, changes.changed
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Reads the records of all JSON files below the root keyed by their path relative to the root
+fn read_tree(root: &Path) -> Result<BTreeMap<String, Records>> {
+    let mut tree = BTreeMap::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).wrap_err_with(|| format!("failed to read {}", dir.display()))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                let json = fs::read_to_string(&path)?;
+                let records = serde_json::from_str::<Vec<Map<String, Value>>>(&json)
+                    .wrap_err_with(|| format!("failed to parse {}", path.display()))?;
+                let name = path.strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+                tree.insert(name, to_records(records));
+            }
+        }
+    }
+    Ok(tree)
+}
+
+/// Keys the records by URL, records without URL are keyed by their content
+fn to_records(records: Vec<Map<String, Value>>) -> Records {
+    records
+        .into_iter()
+        .map(|record| match record.get("url").and_then(Value::as_str) {
+            Some(url) => (url.to_string(), record),
+            None => (Value::Object(record.clone()).to_string(), record),
+        })
+        .collect()
+}
+
+/// Returns the changes of each file which differs
+fn diff_by_file(old: &BTreeMap<String, Records>, new: &BTreeMap<String, Records>) -> BTreeMap<String, Changes> {
+    let empty = Records::new();
+    old.keys()
+        .chain(new.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|name| {
+            let changes = diff_records(old.get(name).unwrap_or(&empty), new.get(name).unwrap_or(&empty));
+            (name.clone(), changes)
+        })
+        .filter(|(_, changes)| !changes.is_empty())
+        .collect()
+}
+
+/// Returns the changes of each vendor whose records differ
+///
+/// The vendor is taken from the records, or from the first directory of the file if it was not exported.
+fn diff_by_vendor(old: &BTreeMap<String, Records>, new: &BTreeMap<String, Records>) -> BTreeMap<String, Changes> {
+    let old = group_by_vendor(old);
+    let new = group_by_vendor(new);
+    let empty = Records::new();
+    old.keys()
+        .chain(new.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|vendor| {
+            let changes = diff_records(old.get(vendor).unwrap_or(&empty), new.get(vendor).unwrap_or(&empty));
+            (vendor.clone(), changes)
+        })
+        .filter(|(_, changes)| !changes.is_empty())
+        .collect()
+}
+
+fn group_by_vendor(tree: &BTreeMap<String, Records>) -> BTreeMap<String, Records> {
+    let mut vendors: BTreeMap<String, Records> = BTreeMap::new();
+    for (name, records) in tree {
+        let dir = name.split('/').next().unwrap_or_default();
+        for (key, record) in records {
+            let vendor = record.get("vendor").and_then(Value::as_str).unwrap_or(dir);
+            vendors
+                .entry(vendor.to_string())
+                .or_default()
+                .insert(key.clone(), record.clone());
+        }
+    }
+    vendors
+}
+
+fn diff_records(old: &Records, new: &Records) -> Changes {
+    let mut changes = Changes::default();
+    for (key, record) in new {
+        match old.get(key) {
+            Some(previous) if previous != record => changes.changed += 1,
+            Some(_) => {}
+            None => changes.added += 1,
+        }
+    }
+    changes.removed = old.keys().filter(|key| !new.contains_key(*key)).count();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    fn write_tree(root: &Path, files: &[(&str, Value)]) {
+        for (name, records) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, records.to_string()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let root = TempDir::new("diff");
+        let temurin = json!([
+            {"url": "https://example.com/temurin-21.tar.gz", "vendor": "temurin", "version": "21.0.1"},
+        ]);
+        write_tree(
+            &root.join("old"),
+            &[
+                ("temurin/linux/x86_64.json", temurin.clone()),
+                (
+                    "zulu/linux/x86_64.json",
+                    json!([
+                        {"url": "https://example.com/zulu-17.tar.gz", "vendor": "zulu", "checksum": null},
+                        {"url": "https://example.com/zulu-21.tar.gz", "vendor": "zulu"},
+                    ]),
+                ),
+            ],
+        );
+        write_tree(
+            &root.join("new"),
+            &[
+                ("temurin/linux/x86_64.json", temurin),
+                (
+                    "zulu/linux/x86_64.json",
+                    json!([
+                        {"url": "https://example.com/zulu-17.tar.gz", "vendor": "zulu", "checksum": "sha256:abc"},
+                        {"url": "https://example.com/zulu-25.tar.gz", "vendor": "zulu"},
+                    ]),
+                ),
+            ],
+        );
+
+        let old = read_tree(&root.join("old")).unwrap();
+        let new = read_tree(&root.join("new")).unwrap();
+        let expected = Changes {
+            added: 1,
+            removed: 1,
+            changed: 1,
+        };
+
+        let by_vendor = diff_by_vendor(&old, &new);
+        assert_eq!(by_vendor.keys().collect::<Vec<_>>(), vec!["zulu"]);
+        assert_eq!(by_vendor["zulu"], expected);
+
+        let by_file = diff_by_file(&old, &new);
+        assert_eq!(by_file.keys().collect::<Vec<_>>(), vec!["zulu/linux/x86_64.json"]);
+        assert_eq!(by_file["zulu/linux/x86_64.json"], expected);
+
+        assert!(diff_by_vendor(&old, &old).is_empty());
+    }
+}
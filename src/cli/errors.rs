@@ -0,0 +1,80 @@
+use eyre::Result;
+use log::warn;
+
+/// Flags choosing how a command handles the failure of a single vendor or file
+#[derive(Clone, Copy, Debug, Default, clap::Args)]
+pub struct ErrorArgs {
+    /// Stop at the first vendor or file which fails instead of continuing with the remaining ones
+    #[clap(long, default_value = "false", conflicts_with = "ignore_errors")]
+    pub fail_fast: bool,
+    /// Exit with 0 even if vendors or files failed, the failures are still logged
+    #[clap(long, default_value = "false")]
+    pub ignore_errors: bool,
+}
+
+impl ErrorArgs {
+    pub fn policy(self) -> ErrorPolicy {
+        match (self.fail_fast, self.ignore_errors) {
+            (true, _) => ErrorPolicy::FailFast,
+            (false, true) => ErrorPolicy::Ignore,
+            (false, false) => ErrorPolicy::Collect,
+        }
+    }
+}
+
+/// How a command handles the failure of a single vendor or file
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ErrorPolicy {
+    /// Failures are collected, the command continues and fails once done
+    #[default]
+    Collect,
+    /// The first failure stops the command
+    FailFast,
+    /// Failures are collected and logged, the command succeeds
+    Ignore,
+}
+
+impl ErrorPolicy {
+    /// Returns whether a failure stops the command before the remaining vendors or files are processed
+    pub fn aborts(self) -> bool {
+        self == ErrorPolicy::FailFast
+    }
+
+    /// Returns the outcome of a command from the result of its collected failures
+    pub fn finish(self, result: Result<()>) -> Result<()> {
+        match (self, result) {
+            (ErrorPolicy::Ignore, Err(err)) => {
+                warn!("ignoring failures: {err}");
+                Ok(())
+            }
+            (_, result) => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy() {
+        for (fail_fast, ignore_errors, expected) in [
+            (false, false, ErrorPolicy::Collect),
+            (true, false, ErrorPolicy::FailFast),
+            (false, true, ErrorPolicy::Ignore),
+        ] {
+            let args = ErrorArgs {
+                fail_fast,
+                ignore_errors,
+            };
+            assert_eq!(args.policy(), expected);
+        }
+        assert!(ErrorPolicy::FailFast.aborts());
+        assert!(!ErrorPolicy::Collect.aborts());
+
+        assert!(ErrorPolicy::Collect.finish(Err(eyre::eyre!("failed"))).is_err());
+        assert!(ErrorPolicy::FailFast.finish(Err(eyre::eyre!("failed"))).is_err());
+        assert!(ErrorPolicy::Ignore.finish(Err(eyre::eyre!("failed"))).is_ok());
+        assert!(ErrorPolicy::Collect.finish(Ok(())).is_ok());
+    }
+}
@@ -1,13 +1,33 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
 
 use clap::Subcommand;
+use flate2::{Compression, write::GzEncoder};
+use itertools::Itertools;
+use log::{error, info};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use serde_json::{Map, Value, ser::PrettyFormatter};
+use versions::Versioning;
+
+use super::{
+    errors::{ErrorArgs, ErrorPolicy},
+    filters::{FilterArgs, read_list_file},
+};
+use crate::jvm::{JvmData, compare_versions, vendor::FILE_TYPES};
 
 mod release_type;
+mod split;
 mod vendor;
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     ReleaseType(release_type::ReleaseType),
+    Split(split::Split),
     Vendor(vendor::Vendor),
 }
 
@@ -15,6 +35,7 @@ impl Commands {
     pub fn run(self) -> eyre::Result<()> {
         match self {
             Self::ReleaseType(cmd) => cmd.run(),
+            Self::Split(cmd) => cmd.run(),
             Self::Vendor(cmd) => cmd.run(),
         }
     }
@@ -33,16 +54,1129 @@ impl Export {
     }
 }
 
-fn get_filter_map(filters: Vec<String>) -> HashMap<String, Vec<String>> {
-    let mut map: HashMap<String, Vec<String>> = HashMap::new();
-    for filter in filters {
-        let parts: Vec<&str> = filter.split('=').collect();
-        if parts.len() != 2 {
+/// Flags shared by the export subcommands
+#[derive(Debug, clap::Args)]
+pub struct ExportArgs {
+    /// Properties to include e.g.: checksum, features, release_type, vendor, version
+    #[clap(short = 'i', long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY")]
+    pub include: Option<Vec<String>>,
+    /// Properties to exclude e.g.: architecture, os, size
+    #[clap(short = 'e', long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY")]
+    pub exclude: Option<Vec<String>>,
+    #[clap(flatten)]
+    pub filters: FilterArgs,
+    /// File types to export e.g.: tar.gz, zip
+    #[clap(long, num_args = 0.., value_delimiter = ',', value_name = "EXTENSION")]
+    pub extensions: Option<Vec<String>>,
+    /// Properties to export in the given order e.g.: version, url, checksum
+    #[clap(long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY", conflicts_with_all = ["include", "exclude"])]
+    pub fields: Option<Vec<String>>,
+    /// Names under which properties are exported e.g.: java_version=jdk_version, overrides export.renames
+    #[clap(long, num_args = 0.., value_delimiter = ',', value_name = "OLD=NEW")]
+    pub rename: Option<Vec<String>>,
+    /// Include JRE images which are skipped by default unless a filter selects image types
+    #[clap(long, default_value = "false")]
+    pub include_jre: bool,
+    /// Export only the URL and checksum of records with a checksum
+    #[clap(long, default_value = "false", conflicts_with_all = ["include", "exclude", "fields"])]
+    pub checksum_only: bool,
+    /// Export features as a single comma separated string instead of an array e.g. for flat consumers
+    #[clap(long, default_value = "false", conflicts_with = "checksum_only")]
+    pub compact_features: bool,
+    /// Pretty print JSON
+    #[clap(long, default_value = "false")]
+    pub pretty: bool,
+    /// Indentation of pretty printed JSON, a number of spaces or tab e.g.: 4
+    #[clap(long, value_name = "INDENT", value_parser = parse_indent, requires = "pretty")]
+    pub indent: Option<String>,
+    /// Additionally write gzip compressed files (.json.gz) with the given level from 0 (none) to 9 (best)
+    #[clap(long, value_name = "LEVEL", value_parser = clap::value_parser!(u32).range(0..=9))]
+    pub gzip_level: Option<u32>,
+    /// Number of threads used to compress the exported files, all available cores by default
+    #[clap(long, default_value = "0", value_name = "THREADS", requires = "gzip_level")]
+    pub threads: usize,
+    /// Skip writing (and remove existing) files without any records
+    #[clap(long, default_value = "false")]
+    pub prune_empty: bool,
+    /// Only write files whose content changed
+    #[clap(long, default_value = "false")]
+    pub changed_only: bool,
+    /// Only export the records with the latest version of each vendor and major Java version
+    #[clap(long, default_value = "false")]
+    pub only_latest: bool,
+    #[clap(flatten)]
+    pub errors: ErrorArgs,
+}
+
+/// Range of the records of each exported file, all records by default
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Page {
+    pub limit: Option<u64>,
+    pub offset: u64,
+}
+
+impl Page {
+    /// Returns the records of the page ordered by URL
+    fn apply(&self, mut data: Vec<JvmData>) -> Vec<JvmData> {
+        if *self == Page::default() {
+            return data;
+        }
+        data.sort_by(|a, b| a.url.cmp(&b.url));
+        let to_usize = |n: u64| usize::try_from(n).unwrap_or(usize::MAX);
+        data.into_iter()
+            .skip(to_usize(self.offset))
+            .take(self.limit.map_or(usize::MAX, to_usize))
+            .collect()
+    }
+}
+
+/// Filters, maps and writes the records of each exported file as set by the [`ExportArgs`]
+struct Pipeline {
+    args: ExportArgs,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    renames: BTreeMap<String, String>,
+    indent: Option<String>,
+    filters: HashMap<String, Vec<String>>,
+    include_jre: bool,
+    extensions: Vec<String>,
+    page: Page,
+    /// Directory of the export below which empty files are pruned
+    root: PathBuf,
+    summary: ExportSummary,
+}
+
+impl Pipeline {
+    fn new(mut args: ExportArgs, root: PathBuf, renames: Option<HashMap<String, String>>) -> eyre::Result<Self> {
+        let filters = args.filters.filter_map()?;
+        Ok(Pipeline {
+            include: args.include.take().unwrap_or_default(),
+            exclude: args.exclude.take().unwrap_or_default(),
+            renames: get_renames(args.rename.take(), renames)?,
+            indent: get_indent(args.pretty, args.indent.take()),
+            include_jre: includes_jre(args.include_jre, &filters),
+            filters,
+            page: Page::default(),
+            extensions: validate_extensions(args.extensions.take())?,
+            root,
+            summary: ExportSummary::new(args.errors.policy()),
+            args,
+        })
+    }
+
+    /// Exports only the given page of the filtered records of each file
+    fn with_page(mut self, page: Page) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Returns the filtered records mapped to the exported properties
+    fn map(&self, data: Vec<JvmData>) -> Vec<Map<String, Value>> {
+        let data = data
+            .into_par_iter()
+            .filter(|item| self.include_jre || !is_jre(item))
+            .filter(|item| JvmData::filter(item, &self.filters))
+            .filter(|item| has_extension(item, &self.extensions))
+            .collect::<Vec<JvmData>>();
+        let data = match self.args.only_latest {
+            true => keep_latest(data),
+            false => data,
+        };
+        let data = self.page.apply(data);
+
+        data.into_par_iter()
+            .filter_map(|item| match (&self.args.fields, self.args.checksum_only) {
+                (_, true) => to_checksum_entry(&item),
+                (Some(fields), false) => Some(JvmData::map_fields(&item, fields)),
+                (None, false) => Some(JvmData::map(&item, &self.include, &self.exclude)),
+            })
+            .map(|map| match self.args.compact_features {
+                true => JvmData::compact_features(map),
+                false => map,
+            })
+            .map(|map| JvmData::rename(map, &self.renames))
+            .collect()
+    }
+
+    /// Exports the records to the file at `path`, `-` writes to stdout
+    ///
+    /// An empty file is removed instead if empty files are pruned. A failed write fails the export only if it fails
+    /// fast, otherwise it is recorded and reported once the export finishes.
+    fn export(&mut self, path: PathBuf, data: Vec<JvmData>) -> eyre::Result<()> {
+        let export_data = self.map(data);
+        let size = export_data.len();
+        if self.args.prune_empty && size == 0 {
+            info!("pruning empty {}", path.display());
+            prune_file(&self.root, &path)?;
+            if self.args.gzip_level.is_some() {
+                prune_file(&self.root, &get_gzip_path(&path))?;
+            }
+            return Ok(());
+        }
+
+        info!("exporting {size} records to {}", path.display());
+        let indent = self.indent.as_deref();
+        match self.args.changed_only {
+            true => {
+                let result = write_json_if_changed(&path, &export_data, indent);
+                self.summary.record_changed(path, result)
+            }
+            false => {
+                let result = write_output(&path, &export_data, indent, io::stdout().lock());
+                self.summary.record(path, result)
+            }
+        }
+    }
+
+    /// Compresses the written files if requested and returns the outcome of the export
+    fn finish(mut self) -> eyre::Result<()> {
+        if let Some(level) = self.args.gzip_level {
+            let paths = self.summary.written.clone();
+            info!("compressing {} files with level {level}", paths.len());
+            for (path, result) in write_gzips(&paths, level, self.args.threads)? {
+                self.summary.record(path, result)?;
+            }
+        }
+        self.summary.finish()
+    }
+}
+
+/// Supported layouts of the exported files, `{key}` is replaced by the property the export is keyed by
+const OUTPUT_LAYOUTS: [&str; 4] = [
+    "{key}/{os}/{arch}.json",
+    "{key}/{os}-{arch}.json",
+    "{os}/{arch}/{key}.json",
+    "{key}-{os}-{arch}.json",
+];
+
+/// Returns the validated output layout for an export keyed by `key` e.g. `vendor` or `release_type`
+fn get_output_layout(layout: Option<String>, key: &str) -> eyre::Result<String> {
+    let layouts = OUTPUT_LAYOUTS
+        .iter()
+        .map(|l| l.replace("{key}", &format!("{{{key}}}")))
+        .collect::<Vec<_>>();
+    match layout {
+        None => Ok(layouts[0].clone()),
+        Some(layout) if layouts.contains(&layout) => Ok(layout),
+        Some(layout) => Err(eyre::eyre!(
+            "unsupported output layout {layout}, supported layouts are: {}",
+            layouts.join(", ")
+        )),
+    }
+}
+
+/// Returns the path of an export file by replacing the placeholders of the layout with the given values
+fn get_export_path(root: &Path, layout: &str, values: &[(&str, &str)]) -> PathBuf {
+    let mut relative_path = layout.to_string();
+    for (key, value) in values {
+        relative_path = relative_path.replace(&format!("{{{key}}}"), value);
+    }
+    root.join(relative_path)
+}
+
+/// Returns the validated extensions e.g. `.TAR.GZ` becomes `tar.gz`
+fn validate_extensions(extensions: Option<Vec<String>>) -> eyre::Result<Vec<String>> {
+    extensions
+        .unwrap_or_default()
+        .into_iter()
+        .map(|extension| {
+            let extension = extension.trim().trim_start_matches('.').to_lowercase();
+            match FILE_TYPES.contains(&extension.as_str()) {
+                true => Ok(extension),
+                false => Err(eyre::eyre!(
+                    "unsupported extension {extension}, supported extensions are: {}",
+                    FILE_TYPES.join(", ")
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Returns whether the file type of the record is one of the extensions, all match if empty
+fn has_extension(item: &JvmData, extensions: &[String]) -> bool {
+    extensions.is_empty() || extensions.contains(&item.file_type)
+}
+
+/// Returns the URL and strongest checksum of the record, none if it has no checksum
+fn to_checksum_entry(item: &JvmData) -> Option<Map<String, Value>> {
+    let checksum = item.get_checksum()?;
+    Some(Map::from_iter([
+        ("url".to_string(), Value::String(item.url.clone())),
+        ("checksum".to_string(), Value::String(checksum)),
+    ]))
+}
+
+/// Keeps the records with the highest version of each vendor and major Java version and drops older releases
+fn keep_latest(data: Vec<JvmData>) -> Vec<JvmData> {
+    let mut latest: HashMap<(&str, Option<u64>), &str> = HashMap::new();
+    for item in &data {
+        if Versioning::new(&item.version).is_none() {
             continue;
         }
-        let key = parts[0].to_string();
-        let value = parts[1].split(",").map(|s| s.to_string()).collect::<Vec<_>>();
-        map.entry(key).or_default().extend(value);
+        let key = (item.vendor.as_str(), get_major(&item.java_version));
+        if latest
+            .get(&key)
+            .is_none_or(|current| compare_versions(&item.version, current).is_gt())
+        {
+            latest.insert(key, &item.version);
+        }
+    }
+    let keep = data
+        .iter()
+        .map(|item| {
+            let key = (item.vendor.as_str(), get_major(&item.java_version));
+            match (latest.get(&key), Versioning::new(&item.version)) {
+                (Some(latest), Some(_)) => item.version == *latest,
+                // records with an unparsable version are kept
+                _ => true,
+            }
+        })
+        .collect::<Vec<_>>();
+    data.into_iter()
+        .zip(keep)
+        .filter_map(|(item, keep)| keep.then_some(item))
+        .collect()
+}
+
+/// Returns the major of a Java version e.g. 8 for 1.8.0_392, 17 for 17.0.10+7
+fn get_major(java_version: &str) -> Option<u64> {
+    let mut numbers = java_version
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|n| n.parse::<u64>().ok());
+    match numbers.next()? {
+        Some(1) => numbers.next().flatten().or(Some(1)),
+        major => major,
+    }
+}
+
+/// Returns whether JRE images are exported, they are skipped by default unless a filter selects image types
+fn includes_jre(include_jre: bool, filters: &HashMap<String, Vec<String>>) -> bool {
+    include_jre || filters.contains_key("image_type")
+}
+
+/// Returns whether the record is a JRE image
+fn is_jre(item: &JvmData) -> bool {
+    item.image_type == "jre"
+}
+
+/// Returns the OS a record is exported under, musl Linux builds use `musl_os` if configured
+fn get_export_os<'a>(item: &'a JvmData, musl_os: Option<&'a str>) -> &'a str {
+    match musl_os {
+        Some(musl_os)
+            if item.os == "linux" && item.features.as_ref().is_some_and(|f| f.iter().any(|f| f == "musl")) =>
+        {
+            musl_os
+        }
+        _ => &item.os,
+    }
+}
+
+/// Returns the OS stored in the database for an exported OS
+fn get_db_os<'a>(os: &'a str, musl_os: Option<&str>) -> &'a str {
+    match musl_os {
+        Some(musl_os) if os == musl_os => "linux",
+        _ => os,
+    }
+}
+
+/// Returns the OSes extended by `musl_os` if configured and Linux is part of the OSes
+pub(crate) fn split_musl_os(mut oses: Vec<String>, musl_os: Option<&str>) -> Vec<String> {
+    if let Some(musl_os) = musl_os
+        && oses.iter().any(|os| os == "linux")
+        && !oses.iter().any(|os| os == musl_os)
+    {
+        oses.push(musl_os.to_string());
+        oses.sort();
+    }
+    oses
+}
+
+/// Returns the configured renames overridden by the given `old=new` renames
+fn get_renames(
+    renames: Option<Vec<String>>,
+    configured: Option<HashMap<String, String>>,
+) -> eyre::Result<BTreeMap<String, String>> {
+    let mut result = configured.unwrap_or_default().into_iter().collect::<BTreeMap<_, _>>();
+    for rename in renames.unwrap_or_default() {
+        match rename.split_once('=') {
+            Some((old, new)) if !old.trim().is_empty() && !new.trim().is_empty() => {
+                result.insert(old.trim().to_string(), new.trim().to_string());
+            }
+            _ => return Err(eyre::eyre!("invalid rename {rename}, expected old=new")),
+        }
+    }
+    // a renamed property must neither replace one which is kept nor another renamed one
+    let properties = JvmData::property_names();
+    let mut targets = HashSet::new();
+    for (old, new) in &result {
+        if !targets.insert(new) {
+            return Err(eyre::eyre!("rename {old}={new} collides with another rename to {new}"));
+        }
+        if properties.contains(new) && !result.contains_key(new) {
+            return Err(eyre::eyre!("rename {old}={new} collides with the property {new}"));
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the given values extended by the values of the file with one value per line, none if neither is given
+///
+/// Fails if the file and the given values are both empty as nothing would be exported.
+fn get_values(values: Option<Vec<String>>, file: Option<&Path>, kind: &str) -> eyre::Result<Option<Vec<String>>> {
+    let Some(path) = file else {
+        return Ok(values);
+    };
+    let mut result = values.unwrap_or_default();
+    for value in read_list_file(path, kind)? {
+        if !result.contains(&value) {
+            result.push(value);
+        }
+    }
+    if result.is_empty() {
+        return Err(eyre::eyre!("{kind} file {} has no values", path.display()));
+    }
+    Ok(Some(result))
+}
+
+/// Indentation of pretty printed JSON unless set by --indent
+const DEFAULT_INDENT: &str = "  ";
+
+/// Parses the indentation of pretty printed JSON, a number of spaces or `tab`
+fn parse_indent(indent: &str) -> Result<String, String> {
+    match indent {
+        "tab" => Ok("\t".to_string()),
+        _ => match indent.parse::<usize>() {
+            Ok(spaces) if spaces <= 16 => Ok(" ".repeat(spaces)),
+            _ => Err(format!(
+                "invalid indent {indent}, expected a number of spaces up to 16 or tab"
+            )),
+        },
+    }
+}
+
+/// Returns the indentation of the JSON files, none if they are not pretty printed
+fn get_indent(pretty: bool, indent: Option<String>) -> Option<String> {
+    pretty.then(|| indent.unwrap_or_else(|| DEFAULT_INDENT.to_string()))
+}
+
+/// Writes the data as compact JSON or pretty printed with the given indentation
+fn to_json_writer<W: Write, T: Serialize>(writer: W, data: &T, indent: Option<&str>) -> eyre::Result<()> {
+    match indent {
+        Some(indent) => {
+            let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+            data.serialize(&mut serde_json::Serializer::with_formatter(writer, formatter))?
+        }
+        None => serde_json::to_writer(writer, data)?,
+    }
+    Ok(())
+}
+
+/// Writes the data as JSON to a temporary file next to `path` and atomically renames it to `path`
+///
+/// Consumers of the export tree will therefore never see a partially written file.
+fn write_json<T: Serialize>(path: &Path, data: &T, indent: Option<&str>) -> eyre::Result<()> {
+    write_atomic(path, |writer| to_json_writer(writer, data, indent))
+}
+
+/// Output path writing to stdout instead of a file e.g. `--output -`
+const STDOUT: &str = "-";
+
+/// Writes the data as JSON to the output of `--output`, `stdout` for `-` or like [`write_json`] to a file otherwise
+fn write_output<T: Serialize, W: Write>(
+    path: &Path,
+    data: &T,
+    indent: Option<&str>,
+    mut stdout: W,
+) -> eyre::Result<()> {
+    if path.as_os_str() != STDOUT {
+        return write_json(path, data, indent);
+    }
+    to_json_writer(&mut stdout, data, indent)?;
+    writeln!(stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Writes the data as JSON like [`write_json`] unless the file at `path` already has the same content
+///
+/// Returns whether the file was written.
+fn write_json_if_changed<T: Serialize>(path: &Path, data: &T, indent: Option<&str>) -> eyre::Result<bool> {
+    let mut json = Vec::new();
+    to_json_writer(&mut json, data, indent)?;
+    if fs::read(path).is_ok_and(|existing| existing == json) {
+        return Ok(false);
+    }
+    write_atomic(path, |writer| Ok(writer.write_all(&json)?))?;
+    Ok(true)
+}
+
+/// Writes a gzip compressed copy of the file at `path` to `path.gz`
+fn write_gzip(path: &Path, level: u32) -> eyre::Result<()> {
+    let data = fs::read(path)?;
+    write_atomic(&get_gzip_path(path), |writer| {
+        let mut encoder = GzEncoder::new(writer, Compression::new(level));
+        encoder.write_all(&data)?;
+        encoder.finish()?;
+        Ok(())
+    })
+}
+
+/// Writes gzip compressed copies of the files using the given number of threads, all available cores if 0
+///
+/// Returns the result of each compressed file.
+fn write_gzips(paths: &[PathBuf], level: u32, threads: usize) -> eyre::Result<Vec<(PathBuf, eyre::Result<()>)>> {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+    Ok(pool.install(|| {
+        paths
+            .par_iter()
+            .map(|path| (get_gzip_path(path), write_gzip(path, level)))
+            .collect()
+    }))
+}
+
+/// Outcome of the file writes of an export
+///
+/// A failed write is recorded and the export continues with the remaining files unless it fails fast.
+#[derive(Debug, Default)]
+struct ExportSummary {
+    errors: ErrorPolicy,
+    written: Vec<PathBuf>,
+    unchanged: usize,
+    failed: Vec<(PathBuf, eyre::Report)>,
+}
+
+impl ExportSummary {
+    fn new(errors: ErrorPolicy) -> Self {
+        ExportSummary {
+            errors,
+            ..Default::default()
+        }
+    }
+
+    /// Records the result of writing the file at `path`, fails if the export fails fast
+    fn record(&mut self, path: PathBuf, result: eyre::Result<()>) -> eyre::Result<()> {
+        match result {
+            Ok(()) => self.written.push(path),
+            Err(err) if self.errors.aborts() => {
+                return Err(err.wrap_err(format!("failed to write {}", path.display())));
+            }
+            Err(err) => {
+                error!("failed to write {}: {err}", path.display());
+                self.failed.push((path, err));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the result of writing the file at `path` only if its content changed
+    fn record_changed(&mut self, path: PathBuf, result: eyre::Result<bool>) -> eyre::Result<()> {
+        match result {
+            Ok(false) => {
+                self.unchanged += 1;
+                Ok(())
+            }
+            result => self.record(path, result.map(|_| ())),
+        }
+    }
+
+    /// Logs the summary and returns an error if any file failed to be written unless errors are ignored
+    fn finish(self) -> eyre::Result<()> {
+        let errors = self.errors;
+        errors.finish(self.result())
+    }
+
+    fn result(self) -> eyre::Result<()> {
+        info!(
+            "wrote {} files, {} unchanged, {} failed",
+            self.written.len(),
+            self.unchanged,
+            self.failed.len()
+        );
+        match self.failed.is_empty() {
+            true => Ok(()),
+            false => Err(eyre::eyre!(
+                "failed to write {} files: {}",
+                self.failed.len(),
+                self.failed
+                    .iter()
+                    .map(|(path, _)| path.display().to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Writes to a temporary file next to `path` and atomically renames it to `path`
+fn write_atomic<F>(path: &Path, write: F) -> eyre::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> eyre::Result<()>,
+{
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = get_tmp_path(path);
+    let result = (|| -> eyre::Result<()> {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write(&mut writer)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            fs::rename(&tmp_path, path)?;
+            Ok(())
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(err)
+        }
+    }
+}
+
+/// Removes the file at `path` as well as any of its parent directories below `root` which are left empty
+fn prune_file(root: &Path, path: &Path) -> eyre::Result<()> {
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) {
+            break;
+        }
+        let is_empty = fs::read_dir(current).is_ok_and(|mut entries| entries.next().is_none());
+        if !is_empty {
+            break;
+        }
+        fs::remove_dir(current)?;
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+/// Returns the path of the gzip compressed copy of `path` e.g. `linux/x86_64.json.gz`
+fn get_gzip_path(path: &Path) -> PathBuf {
+    let filename = path.file_name().map(|f| f.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!("{filename}.gz"))
+}
+
+/// Returns the path of the temporary file used while writing `path` e.g. `linux/.x86_64.json.tmp`
+fn get_tmp_path(path: &Path) -> PathBuf {
+    let filename = path.file_name().map(|f| f.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!(".{filename}.tmp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use serde_json::{Value, json};
+
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    /// Returns the export arguments parsed from the given command line arguments
+    pub(super) fn export_args(args: &[&str]) -> ExportArgs {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[clap(flatten)]
+            export: ExportArgs,
+        }
+        <Cli as clap::Parser>::parse_from(std::iter::once("export").chain(args.iter().copied())).export
+    }
+
+    #[test]
+    fn test_map_includes_jre() {
+        let records = ["jdk", "jre"].map(|image_type| JvmData {
+            image_type: image_type.to_string(),
+            url: format!("https://example.com/temurin-21-{image_type}.tar.gz"),
+            ..Default::default()
+        });
+        let image_types = |args: &[&str]| {
+            let pipeline = Pipeline::new(export_args(args), PathBuf::new(), None).unwrap();
+            pipeline
+                .map(records.to_vec())
+                .iter()
+                .map(|map| map["image_type"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(image_types(&[]), ["jdk"]);
+        assert_eq!(image_types(&["--include-jre"]), ["jdk", "jre"]);
+        // a filter on the image type selects JRE images without --include-jre
+        assert_eq!(image_types(&["--filters", "image_type=jre"]), ["jre"]);
+        assert_eq!(image_types(&["--filters", "image_type=jre,jdk"]), ["jdk", "jre"]);
+    }
+
+    #[test]
+    fn test_page() {
+        let data = [3, 1, 2].map(|n| JvmData {
+            url: format!("https://example.com/jdk-{n}.tar.gz"),
+            ..Default::default()
+        });
+        let urls = |page: Page| {
+            page.apply(data.to_vec())
+                .into_iter()
+                .map(|item| item.url.chars().nth(24).unwrap())
+                .collect::<String>()
+        };
+        assert_eq!(urls(Page::default()), "312");
+        assert_eq!(
+            urls(Page {
+                limit: Some(2),
+                offset: 0
+            }),
+            "12"
+        );
+        assert_eq!(
+            urls(Page {
+                limit: Some(2),
+                offset: 2
+            }),
+            "3"
+        );
+        assert_eq!(urls(Page { limit: None, offset: 1 }), "23");
+        assert_eq!(
+            urls(Page {
+                limit: Some(u64::MAX),
+                offset: u64::MAX
+            }),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_keep_latest() {
+        let data = [
+            ("17", "17.0.1", "jdk"),
+            ("17", "17.0.3", "jdk"),
+            ("17", "17.0.3", "jre"),
+            ("21", "21.0.1+9", "jdk"),
+            ("21", "21.0.1+12", "jdk"),
+            ("1.8.0_392", "8.0.392", "jdk"),
+            ("8", "8.0.402", "jdk"),
+        ]
+        .map(|(java_version, version, image_type)| JvmData {
+            java_version: java_version.to_string(),
+            version: version.to_string(),
+            image_type: image_type.to_string(),
+            url: format!("https://example.com/{version}-{image_type}.tar.gz"),
+            ..Default::default()
+        })
+        .to_vec();
+
+        let versions = keep_latest(data)
+            .iter()
+            .map(|item| format!("{}-{}", item.version, item.image_type))
+            .collect::<Vec<_>>();
+        // builds of the same version are ordered by their build number
+        assert_eq!(
+            versions,
+            vec!["17.0.3-jdk", "17.0.3-jre", "21.0.1+12-jdk", "8.0.402-jdk"]
+        );
+    }
+
+    #[test]
+    fn test_keep_latest_by_vendor() {
+        let data = [
+            ("temurin", "21.0.1+12"),
+            ("temurin", "21.0.2+13"),
+            ("zulu", "21.0.1+12"),
+            ("zulu", "21.0.0+35"),
+        ]
+        .map(|(vendor, version)| JvmData {
+            vendor: vendor.to_string(),
+            java_version: "21".to_string(),
+            version: version.to_string(),
+            url: format!("https://example.com/{vendor}-{version}.tar.gz"),
+            ..Default::default()
+        })
+        .to_vec();
+
+        let versions = keep_latest(data)
+            .iter()
+            .map(|item| format!("{}-{}", item.vendor, item.version))
+            .collect::<Vec<_>>();
+        // the latest release of each vendor is kept even if another vendor has a newer one
+        assert_eq!(versions, vec!["temurin-21.0.2+13", "zulu-21.0.1+12"]);
+    }
+
+    #[test]
+    fn test_get_renames() {
+        let configured = HashMap::from([
+            ("java_version".to_string(), "jdk".to_string()),
+            ("size".to_string(), "bytes".to_string()),
+        ]);
+        let renames = get_renames(Some(vec!["java_version=jdk_version".to_string()]), Some(configured)).unwrap();
+        assert_eq!(
+            renames,
+            BTreeMap::from([
+                ("java_version".to_string(), "jdk_version".to_string()),
+                ("size".to_string(), "bytes".to_string()),
+            ])
+        );
+        assert!(get_renames(None, None).unwrap().is_empty());
+        for invalid in ["java_version", "java_version=", "=jdk_version"] {
+            assert!(get_renames(Some(vec![invalid.to_string()]), None).is_err(), "{invalid}");
+        }
+
+        // renames must not overwrite other properties
+        for colliding in [
+            vec!["os=architecture"],
+            vec!["os=platform", "architecture=platform"],
+            vec!["size=package_kind"],
+        ] {
+            let colliding = colliding.into_iter().map(String::from).collect();
+            assert!(get_renames(Some(colliding), None).is_err());
+        }
+        // swapping two properties does not lose any of them
+        let swapped = get_renames(
+            Some(vec!["os=architecture".to_string(), "architecture=os".to_string()]),
+            None,
+        );
+        assert_eq!(swapped.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_get_major() {
+        for (java_version, expected) in [
+            ("17", Some(17)),
+            ("17.0.10+7", Some(17)),
+            ("1.8.0_392", Some(8)),
+            ("8u392", Some(8)),
+            ("1", Some(1)),
+            ("", None),
+        ] {
+            assert_eq!(get_major(java_version), expected, "{java_version}");
+        }
+    }
+
+    #[test]
+    fn test_write_json() {
+        let dir = TempDir::new("export-write-json");
+        let path = dir.join("temurin").join("linux").join("x86_64.json");
+
+        write_json(&path, &json!([{"version": "21.0.1"}]), None).unwrap();
+        assert!(!get_tmp_path(&path).exists());
+        let actual: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(actual, json!([{"version": "21.0.1"}]));
+
+        // overwrite an existing file
+        write_json(&path, &json!([{"version": "21.0.2"}]), Some(DEFAULT_INDENT)).unwrap();
+        assert!(!get_tmp_path(&path).exists());
+        let actual: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(actual, json!([{"version": "21.0.2"}]));
+
+        // a failed write keeps the existing file and removes the partly written temporary file
+        let result = write_atomic(&path, |writer| {
+            writer.write_all(b"[{\"version\": ")?;
+            writer.flush()?;
+            Err(eyre::eyre!("serialization failed"))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "serialization failed");
+        let actual: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(actual, json!([{"version": "21.0.2"}]));
+        assert!(!get_tmp_path(&path).exists());
+        let files = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(files, 1);
+    }
+
+    #[test]
+    fn test_write_output() {
+        let data = vec![Map::from_iter([(
+            "url".to_string(),
+            Value::String("https://example.com/jdk.tar.gz".to_string()),
+        )])];
+
+        // - writes to stdout, captured here, instead of a file named -
+        let mut stdout = Vec::new();
+        write_output(Path::new(STDOUT), &data, None, &mut stdout).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Vec<Map<String, Value>>>(&stdout).unwrap(),
+            data
+        );
+        assert!(stdout.ends_with(b"]\n"));
+        assert!(!Path::new(STDOUT).exists());
+
+        let dir = TempDir::new("export-output");
+        let path = dir.join("temurin.json");
+        let mut stdout = Vec::new();
+        write_output(&path, &data, Some("  "), &mut stdout).unwrap();
+        assert!(stdout.is_empty());
+        assert_eq!(
+            serde_json::from_str::<Vec<Map<String, Value>>>(&fs::read_to_string(&path).unwrap()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn test_indent() {
+        let dir = TempDir::new("export-indent");
+        let data = json!([{"version": "21.0.1"}]);
+        for (indent, expected) in [
+            ("2", "[\n  {\n    \"version\": \"21.0.1\"\n  }\n]"),
+            ("4", "[\n    {\n        \"version\": \"21.0.1\"\n    }\n]"),
+            ("tab", "[\n\t{\n\t\t\"version\": \"21.0.1\"\n\t}\n]"),
+        ] {
+            let path = dir.join(format!("{indent}.json"));
+            let indent = get_indent(true, Some(parse_indent(indent).unwrap()));
+            write_json(&path, &data, indent.as_deref()).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+        }
+        assert_eq!(get_indent(true, None).as_deref(), Some(DEFAULT_INDENT));
+        assert_eq!(get_indent(false, None), None);
+        assert!(parse_indent("-1").is_err());
+        assert!(parse_indent("tabs").is_err());
+    }
+
+    #[test]
+    fn test_write_gzips() {
+        let dir = TempDir::new("export-write-gzips");
+        let data = (0..500)
+            .map(|i| json!({"url": format!("https://example.com/jdk-{i}.tar.gz"), "version": "21.0.1"}))
+            .collect::<Vec<_>>();
+        let mut sizes = Vec::new();
+        for level in [0, 1, 6, 9] {
+            let path = dir.join(format!("{level}.json"));
+            write_json(&path, &data, None).unwrap();
+            for (_, result) in write_gzips(std::slice::from_ref(&path), level, 2).unwrap() {
+                result.unwrap();
+            }
+
+            let gzip_path = get_gzip_path(&path);
+            let mut decoded = String::new();
+            flate2::read::GzDecoder::new(File::open(&gzip_path).unwrap())
+                .read_to_string(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, fs::read_to_string(&path).unwrap());
+            sizes.push(fs::metadata(&gzip_path).unwrap().len());
+        }
+        assert!(sizes[1..].iter().all(|size| *size < sizes[0]));
+    }
+
+    #[test]
+    fn test_export_summary() {
+        let dir = TempDir::new("export-summary");
+        let ok_path = dir.join("temurin").join("x86_64.json");
+        // a regular file where a directory is expected makes the write fail
+        let blocked = dir.join("zulu");
+        fs::write(&blocked, "").unwrap();
+        let failed_path = blocked.join("x86_64.json");
+
+        let mut summary = ExportSummary::default();
+        summary
+            .record(ok_path.clone(), write_json(&ok_path, &json!([]), None))
+            .unwrap();
+        summary
+            .record(failed_path.clone(), write_json(&failed_path, &json!([]), None))
+            .unwrap();
+
+        assert_eq!(summary.written, vec![ok_path.clone()]);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, failed_path);
+        assert!(ok_path.exists());
+        assert!(!get_tmp_path(&ok_path).exists());
+        assert!(summary.finish().is_err());
+
+        assert!(ExportSummary::default().finish().is_ok());
+
+        // the same failure is only logged if errors are ignored and stops the export if it fails fast
+        let mut summary = ExportSummary::new(ErrorPolicy::Ignore);
+        summary
+            .record(failed_path.clone(), write_json(&failed_path, &json!([]), None))
+            .unwrap();
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.finish().is_ok());
+        let mut summary = ExportSummary::new(ErrorPolicy::FailFast);
+        let result = summary.record(failed_path.clone(), write_json(&failed_path, &json!([]), None));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_write_json_if_changed() {
+        let dir = TempDir::new("export-write-json-if-changed");
+        let paths = [dir.join("ga").join("linux.json"), dir.join("ea").join("linux.json")];
+        let data = json!([{"url": "https://example.com/jdk.tar.gz", "version": "21.0.1"}]);
+
+        let mut summary = ExportSummary::default();
+        for path in &paths {
+            summary
+                .record_changed(path.clone(), write_json_if_changed(path, &data, Some(DEFAULT_INDENT)))
+                .unwrap();
+        }
+        assert_eq!(summary.written.len(), 2);
+
+        // an identical regeneration writes no files
+        let mut summary = ExportSummary::default();
+        for path in &paths {
+            summary
+                .record_changed(path.clone(), write_json_if_changed(path, &data, Some(DEFAULT_INDENT)))
+                .unwrap();
+        }
+        assert_eq!(summary.written.len(), 0);
+        assert_eq!(summary.unchanged, 2);
+
+        let data = json!([{"url": "https://example.com/jdk.tar.gz", "version": "21.0.2"}]);
+        assert!(write_json_if_changed(&paths[0], &data, Some(DEFAULT_INDENT)).unwrap());
+        let actual: Value = serde_json::from_str(&fs::read_to_string(&paths[0]).unwrap()).unwrap();
+        assert_eq!(actual, data);
+    }
+
+    #[test]
+    fn test_prune_file() {
+        let dir = TempDir::new("export-prune-file");
+        let path = dir.join("temurin").join("linux").join("x86_64.json");
+        let sibling = dir.join("temurin").join("macosx").join("x86_64.json");
+
+        write_json(&path, &json!([]), None).unwrap();
+        write_json(&sibling, &json!([]), None).unwrap();
+
+        prune_file(&dir, &path).unwrap();
+        assert!(!path.exists());
+        assert!(!dir.join("temurin").join("linux").exists());
+        assert!(sibling.exists());
+
+        prune_file(&dir, &sibling).unwrap();
+        assert!(!dir.join("temurin").exists());
+        assert!(dir.exists());
+
+        // pruning a file which was never written is a no-op
+        prune_file(&dir, &path).unwrap();
+    }
+
+    #[test]
+    fn test_get_output_layout() {
+        assert_eq!(get_output_layout(None, "vendor").unwrap(), "{vendor}/{os}/{arch}.json");
+        assert_eq!(
+            get_output_layout(Some("{os}/{arch}/{release_type}.json".to_string()), "release_type").unwrap(),
+            "{os}/{arch}/{release_type}.json"
+        );
+        assert!(get_output_layout(Some("{vendor}/{arch}.json".to_string()), "vendor").is_err());
+        assert!(get_output_layout(Some("{release_type}/{os}/{arch}.json".to_string()), "vendor").is_err());
+    }
+
+    #[test]
+    fn test_get_export_path() {
+        let root = Path::new("public/api/jvm");
+        let values = [("vendor", "temurin"), ("os", "linux"), ("arch", "x86_64")];
+        for (layout, expected) in [
+            ("{vendor}/{os}/{arch}.json", "public/api/jvm/temurin/linux/x86_64.json"),
+            ("{vendor}/{os}-{arch}.json", "public/api/jvm/temurin/linux-x86_64.json"),
+            ("{os}/{arch}/{vendor}.json", "public/api/jvm/linux/x86_64/temurin.json"),
+            ("{vendor}-{os}-{arch}.json", "public/api/jvm/temurin-linux-x86_64.json"),
+        ] {
+            let layout = get_output_layout(Some(layout.to_string()), "vendor").unwrap();
+            assert_eq!(get_export_path(root, &layout, &values), PathBuf::from(expected));
+        }
+    }
+
+    #[test]
+    fn test_get_export_os() {
+        let glibc = JvmData {
+            os: "linux".to_string(),
+            ..Default::default()
+        };
+        let musl = JvmData {
+            features: Some(vec!["musl".to_string()]),
+            os: "linux".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(get_export_os(&musl, None), "linux");
+        assert_eq!(get_export_os(&glibc, Some("linux-musl")), "linux");
+        assert_eq!(get_export_os(&musl, Some("linux-musl")), "linux-musl");
+
+        let layout = get_output_layout(None, "vendor").unwrap();
+        let os = get_export_os(&musl, Some("linux-musl"));
+        assert_eq!(get_db_os(os, Some("linux-musl")), "linux");
+        assert_eq!(
+            get_export_path(
+                Path::new("public/api/jvm"),
+                &layout,
+                &[("vendor", "zulu"), ("os", os), ("arch", "x86_64")]
+            ),
+            PathBuf::from("public/api/jvm/zulu/linux-musl/x86_64.json")
+        );
+    }
+
+    #[test]
+    fn test_split_musl_os() {
+        let oses = vec!["linux".to_string(), "windows".to_string()];
+        assert_eq!(split_musl_os(oses.clone(), None), oses);
+        assert_eq!(
+            split_musl_os(oses.clone(), Some("linux-musl")),
+            vec!["linux", "linux-musl", "windows"]
+        );
+        assert_eq!(
+            split_musl_os(vec!["windows".to_string()], Some("linux-musl")),
+            vec!["windows"]
+        );
+        // an explicit linux keeps the musl builds exported under musl_os
+        assert_eq!(
+            split_musl_os(vec!["linux".to_string()], Some("linux-musl")),
+            vec!["linux", "linux-musl"]
+        );
+    }
+
+    #[test]
+    fn test_validate_extensions() {
+        assert_eq!(validate_extensions(None).unwrap(), Vec::<String>::new());
+        assert_eq!(
+            validate_extensions(Some(vec!["tar.gz".to_string(), ".ZIP".to_string()])).unwrap(),
+            vec!["tar.gz", "zip"]
+        );
+        assert!(validate_extensions(Some(vec!["targz".to_string()])).is_err());
+    }
+
+    #[test]
+    fn test_has_extension() {
+        let extensions = validate_extensions(Some(vec!["tar.gz".to_string(), "zip".to_string()])).unwrap();
+        for (file_type, expected) in [("tar.gz", true), ("zip", true), ("msi", false), ("tar.xz", false)] {
+            let item = JvmData {
+                file_type: file_type.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(has_extension(&item, &extensions), expected);
+            assert!(has_extension(&item, &[]));
+        }
+    }
+
+    #[test]
+    fn test_to_checksum_entry() {
+        let items = [
+            JvmData {
+                checksum: Some("sha256:abcd".to_string()),
+                url: "https://example.com/jdk-1.tar.gz".to_string(),
+                ..Default::default()
+            },
+            JvmData {
+                url: "https://example.com/jdk-2.tar.gz".to_string(),
+                ..Default::default()
+            },
+            JvmData {
+                checksum: Some("md5:0123".to_string()),
+                checksums: vec!["sha512:ef01".to_string()],
+                url: "https://example.com/jdk-3.tar.gz".to_string(),
+                ..Default::default()
+            },
+        ];
+        let actual = items.iter().filter_map(to_checksum_entry).collect::<Vec<_>>();
+        assert_eq!(
+            serde_json::to_value(actual).unwrap(),
+            json!([
+                {"url": "https://example.com/jdk-1.tar.gz", "checksum": "sha256:abcd"},
+                {"url": "https://example.com/jdk-3.tar.gz", "checksum": "sha512:ef01"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_get_tmp_path() {
+        assert_eq!(
+            get_tmp_path(Path::new("temurin/linux/x86_64.json")),
+            PathBuf::from("temurin/linux/.x86_64.json.tmp")
+        );
     }
-    map
 }
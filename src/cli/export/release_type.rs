@@ -1,17 +1,16 @@
-use std::{fs::File, path::PathBuf};
+use std::path::PathBuf;
 
 use eyre::Result;
-use log::info;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use serde_json::{Map, Value};
+use log::warn;
 
 use crate::{
     config::Conf,
     db::{jvm_repository::JvmRepository, pool::ConnectionPool},
-    jvm::JvmData,
 };
 
-use super::get_filter_map;
+use super::{
+    ExportArgs, Pipeline, get_db_os, get_export_os, get_export_path, get_output_layout, get_values, split_musl_os,
+};
 
 /// Export by {release_type}/{os}/{architecture}
 ///
@@ -26,82 +25,85 @@ pub struct ReleaseType {
     /// Operating systems e.g.: linux, macosx, windows
     #[clap(short = 'o', long, num_args = 0.., value_delimiter = ',', value_name = "OS")]
     pub os: Option<Vec<String>>,
+    /// File with one operating system per line which are merged with --os, blank lines and lines starting with # are
+    /// skipped
+    #[clap(long, value_name = "FILE")]
+    pub os_file: Option<PathBuf>,
     /// Architectures e.g.: aarch64, arm32, x86_64
     #[clap(short = 'a', long, num_args = 0.., value_delimiter = ',', value_name = "ARCH")]
     pub arch: Option<Vec<String>>,
-    /// Properties to include e.g.: checksum, features, release_type, vendor, version
-    #[clap(short = 'i', long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY")]
-    pub include: Option<Vec<String>>,
-    /// Properties to exclude e.g.: architecture, os, size
-    #[clap(short = 'e', long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY")]
-    pub exclude: Option<Vec<String>>,
-    /// Filters to apply to the data e.g.: file_type=tar.gz,zip&features=musl,javafx,!lite
-    ///
-    /// Filters are separated with '&' and values are separated with ','. The filter will match if
-    /// any of the values match unless the filter is negated with '!'. For example features=musl,javafx,!lite
-    /// matches entries where the array `features` include musl or javafx but not lite. This is mostly useful for
-    /// arrays that can contain multiple values.
-    #[clap(short = 'f', long, num_args = 0.., value_delimiter = '&', value_name = "FILTER")]
-    pub filters: Option<Vec<String>>,
-    /// Pretty print JSON
-    #[clap(long, default_value = "false")]
-    pub pretty: bool,
+    /// File with one architecture per line which are merged with --arch, blank lines and lines starting with # are
+    /// skipped
+    #[clap(long, value_name = "FILE")]
+    pub arch_file: Option<PathBuf>,
+    /// File to write a single release type, OS and architecture to instead of the export path, - writes to stdout
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["gzip_level", "output_layout", "prune_empty", "changed_only"]
+    )]
+    pub output: Option<PathBuf>,
+    /// Layout of the exported files e.g.: {release_type}/{os}/{arch}.json, {release_type}/{os}-{arch}.json, {os}/{arch}/{release_type}.json,
+    /// {release_type}-{os}-{arch}.json
+    #[clap(long, value_name = "LAYOUT")]
+    pub output_layout: Option<String>,
+    #[clap(flatten)]
+    pub export: ExportArgs,
 }
 
 impl ReleaseType {
     pub fn run(self) -> Result<()> {
+        let layout = get_output_layout(self.output_layout, "release_type")?;
         let conf = Conf::try_get()?;
-        if conf.export.path.is_none() {
+        if conf.export.path.is_none() && self.output.is_none() {
             return Err(eyre::eyre!("export.path is not configured"));
         }
         let conn_pool = ConnectionPool::get_pool()?;
         let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
 
         let release_types_default = db.get_distinct("release_type")?;
         let release_types = self.release_type.unwrap_or(release_types_default);
 
         let oses_default = db.get_distinct("os")?;
-        let oses = self.os.unwrap_or(oses_default);
+        let musl_os = conf.export.musl_os.as_deref();
+        let oses = get_values(self.os, self.os_file.as_deref(), "os")?;
+        // musl builds are stored as linux and exported under musl_os along with the glibc builds of linux
+        let oses = split_musl_os(oses.unwrap_or(oses_default), musl_os);
 
         let arch_default = db.get_distinct("architecture")?;
-        let archs = self.arch.unwrap_or(arch_default);
-
-        let include = self.include.unwrap_or_default();
-        let exclude = self.exclude.unwrap_or_default();
-
-        let filters = get_filter_map(self.filters.unwrap_or_default());
+        let archs = get_values(self.arch, self.arch_file.as_deref(), "arch")?.unwrap_or(arch_default);
+        let combinations = release_types.len() * oses.len() * archs.len();
+        if self.output.is_some() && combinations != 1 {
+            return Err(eyre::eyre!(
+                "--output requires a single release type, os and arch, got {combinations} combinations"
+            ));
+        }
 
-        let export_path = conf.export.path.unwrap();
+        let export_path = PathBuf::from(conf.export.path.unwrap_or_default());
+        let mut pipeline = Pipeline::new(self.export, export_path.clone(), conf.export.renames)?;
 
         for release_type in &release_types {
             for os in &oses {
                 for arch in &archs {
-                    let data = db.export_release_type(release_type, arch, os)?;
-
-                    let export_data = data
-                        .into_par_iter()
-                        .filter(|item| JvmData::filter(item, &filters))
-                        .map(|item| JvmData::map(&item, &include, &exclude))
-                        .collect::<Vec<Map<String, Value>>>();
-                    let size = export_data.len();
-
-                    info!("exporting {size} records to {release_type}/{os}/{arch}.json");
-                    let path = PathBuf::from(&export_path)
-                        .join(release_type)
-                        .join(os)
-                        .join(format!("{arch}.json"));
-                    if let Some(parent) = path.parent() {
-                        std::fs::create_dir_all(parent)?;
-                    }
-
-                    let file = File::create(path)?;
-                    match self.pretty {
-                        true => serde_json::to_writer_pretty(file, &export_data)?,
-                        false => serde_json::to_writer(file, &export_data)?,
-                    }
+                    let path = get_export_path(
+                        &export_path,
+                        &layout,
+                        &[("release_type", release_type), ("os", os), ("arch", arch)],
+                    );
+                    let path = self.output.clone().unwrap_or(path);
+                    let data = db
+                        .export_release_type(release_type, arch, get_db_os(os, musl_os))?
+                        .into_iter()
+                        .filter(|item| get_export_os(item, musl_os) == os)
+                        .collect();
+                    pipeline.export(path, data)?;
                 }
             }
         }
-        Ok(())
+        pipeline.finish()
     }
 }
@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+
+use eyre::Result;
+use log::warn;
+
+use crate::{
+    config::Conf,
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+};
+
+use super::{ExportArgs, Pipeline, get_db_os, get_export_os};
+
+/// Properties the export can be split by
+const SPLIT_FIELDS: [&str; 6] = [
+    "architecture",
+    "image_type",
+    "java_version",
+    "os",
+    "release_type",
+    "vendor",
+];
+
+/// Export split by arbitrary properties
+///
+/// Will export JSON files nested by the given properties in the given order e.g. --split-by vendor,image_type
+/// exports files in form of {vendor}/{image_type}.json to the path specified in the configuration file
+/// or ROAST_EXPORT_PATH environment variable
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Split {
+    /// Properties to split by e.g.: vendor, os, architecture, image_type, java_version
+    #[clap(
+        short = 's',
+        long,
+        num_args = 1..,
+        value_delimiter = ',',
+        value_name = "PROPERTY",
+        default_value = "vendor,os,architecture"
+    )]
+    pub split_by: Vec<String>,
+    #[clap(flatten)]
+    pub export: ExportArgs,
+}
+
+impl Split {
+    pub fn run(self) -> Result<()> {
+        let split_by = validate_split_by(&self.split_by)?;
+        let conf = Conf::try_get()?;
+        if conf.export.path.is_none() {
+            return Err(eyre::eyre!("export.path is not configured"));
+        }
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
+
+        let export_path = PathBuf::from(conf.export.path.unwrap());
+        let mut pipeline = Pipeline::new(self.export, export_path.clone(), conf.export.renames)?;
+
+        let musl_os = conf.export.musl_os.as_deref();
+        let os_index = split_by.iter().position(|field| *field == "os");
+        let combinations = db.get_distinct_combinations(&split_by)?;
+        for values in split_musl_combinations(combinations, os_index, musl_os) {
+            // musl builds are stored as linux and filtered by the OS they are exported under
+            let mut db_values = values.clone();
+            if let Some(i) = os_index {
+                db_values[i] = get_db_os(&values[i], musl_os).to_string();
+            }
+            let data = db
+                .export_by(&split_by, &db_values)?
+                .into_iter()
+                .filter(|item| os_index.is_none_or(|i| get_export_os(item, musl_os) == values[i]))
+                .collect();
+            pipeline.export(get_split_path(&export_path, &values), data)?;
+        }
+        pipeline.finish()
+    }
+}
+
+/// Returns the properties to split by if all of them are supported and none is repeated
+fn validate_split_by(split_by: &[String]) -> Result<Vec<&str>> {
+    let mut fields = Vec::new();
+    for field in split_by {
+        let field = SPLIT_FIELDS
+            .iter()
+            .find(|f| **f == field)
+            .ok_or_else(|| eyre::eyre!("can not split by {field}, expected one of: {}", SPLIT_FIELDS.join(", ")))?;
+        if fields.contains(field) {
+            return Err(eyre::eyre!("can not split by {field} more than once"));
+        }
+        fields.push(*field);
+    }
+    if fields.is_empty() {
+        return Err(eyre::eyre!("at least one property to split by is required"));
+    }
+    Ok(fields)
+}
+
+/// Returns the combinations extended by a copy of each one with the linux OS at `os_index` replaced by `musl_os` if
+/// configured
+fn split_musl_combinations(
+    combinations: Vec<Vec<String>>,
+    os_index: Option<usize>,
+    musl_os: Option<&str>,
+) -> Vec<Vec<String>> {
+    let (Some(i), Some(musl_os)) = (os_index, musl_os) else {
+        return combinations;
+    };
+    let mut result = combinations.clone();
+    for values in combinations.into_iter().filter(|values| values[i] == "linux") {
+        let mut musl_values = values;
+        musl_values[i] = musl_os.to_string();
+        if !result.contains(&musl_values) {
+            result.push(musl_values);
+        }
+    }
+    result.sort();
+    result
+}
+
+/// Returns the path of an export file nested by the given values e.g. `{root}/temurin/jdk.json`
+fn get_split_path(root: &Path, values: &[String]) -> PathBuf {
+    let mut path = root.to_path_buf();
+    if let Some((last, parents)) = values.split_last() {
+        for parent in parents {
+            path.push(parent);
+        }
+        path.push(format!("{last}.json"));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_split_by() {
+        let split_by = ["vendor".to_string(), "image_type".to_string()];
+        assert_eq!(validate_split_by(&split_by).unwrap(), vec!["vendor", "image_type"]);
+        assert!(validate_split_by(&[]).is_err());
+        assert!(validate_split_by(&["url".to_string()]).is_err());
+        assert!(validate_split_by(&["os".to_string(), "os".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_split_musl_combinations() {
+        let combinations = [["corretto", "linux"], ["corretto", "windows"], ["zulu", "linux"]]
+            .map(|values| values.map(String::from).to_vec())
+            .to_vec();
+        assert_eq!(
+            split_musl_combinations(combinations.clone(), Some(1), None),
+            combinations
+        );
+        assert_eq!(
+            split_musl_combinations(combinations.clone(), None, Some("linux-musl")),
+            combinations
+        );
+        let expected = [
+            ["corretto", "linux"],
+            ["corretto", "linux-musl"],
+            ["corretto", "windows"],
+            ["zulu", "linux"],
+            ["zulu", "linux-musl"],
+        ]
+        .map(|values| values.map(String::from).to_vec())
+        .to_vec();
+        assert_eq!(
+            split_musl_combinations(combinations, Some(1), Some("linux-musl")),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_get_split_path() {
+        let root = Path::new("public/api/jvm");
+        let combinations = [["corretto", "jdk"], ["temurin", "jdk"], ["temurin", "jre"]];
+        let actual = combinations
+            .iter()
+            .map(|values| get_split_path(root, &values.map(String::from)))
+            .collect::<HashSet<_>>();
+        let expected = [
+            "public/api/jvm/corretto/jdk.json",
+            "public/api/jvm/temurin/jdk.json",
+            "public/api/jvm/temurin/jre.json",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect::<HashSet<_>>();
+        assert_eq!(actual, expected);
+    }
+}
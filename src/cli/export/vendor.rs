@@ -1,9 +1,13 @@
-use std::{fs::File, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+};
 
 use eyre::Result;
-use log::info;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use serde_json::{Map, Value};
+use log::{info, warn};
+use serde::Serialize;
+use versions::Versioning;
 
 use crate::{
     config::Conf,
@@ -11,7 +15,22 @@ use crate::{
     jvm::JvmData,
 };
 
-use super::get_filter_map;
+use super::{
+    ExportArgs, Page, Pipeline, get_db_os, get_export_os, get_export_path, get_indent, get_output_layout, get_values,
+    includes_jre, split_musl_os, write_output,
+};
+
+/// File the facets of the vendors are written to by --group-features
+const FACETS_FILE: &str = "facets.json";
+
+/// Distinct values of the records of a vendor used for faceting
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Facets {
+    os: Vec<String>,
+    architecture: Vec<String>,
+    java_version: Vec<String>,
+    features: Vec<String>,
+}
 
 /// Export by {vendor}/{os}/{architecture}
 ///
@@ -23,80 +42,594 @@ pub struct Vendor {
     /// Vendors e.g.: corretto, oracle, zulu
     #[clap(short = 'v', long, num_args = 0.., value_delimiter = ',', value_name = "VENDOR")]
     pub vendors: Option<Vec<String>>,
+    /// File with one vendor per line which are merged with --vendors, blank lines and lines starting with # are skipped
+    #[clap(long, value_name = "FILE")]
+    pub vendors_file: Option<PathBuf>,
     /// Operating systems e.g.: linux, macosx, windows
     #[clap(short = 'o', long, num_args = 0.., value_delimiter = ',', value_name = "OS")]
     pub os: Option<Vec<String>>,
+    /// File with one operating system per line which are merged with --os, blank lines and lines starting with # are
+    /// skipped
+    #[clap(long, value_name = "FILE")]
+    pub os_file: Option<PathBuf>,
     /// Architectures e.g.: aarch64, arm32, x86_64
     #[clap(short = 'a', long, num_args = 0.., value_delimiter = ',', value_name = "ARCH")]
     pub arch: Option<Vec<String>>,
-    /// Properties e.g.: architecture, os, vendor, version
-    #[clap(short = 'i', long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY")]
-    pub include: Option<Vec<String>>,
-    /// Properties e.g.: architecture, os, vendor, version
-    #[clap(short = 'e', long, num_args = 0.., value_delimiter = ',', value_name = "PROPERTY")]
-    pub exclude: Option<Vec<String>>,
-    /// Filters to apply to the data e.g.: file_type=tar.gz,zip&features=musl,javafx,lite
-    #[clap(short = 'f', long, num_args = 0.., value_delimiter = '&', value_name = "FILTER")]
-    pub filters: Option<Vec<String>>,
-    /// Pretty print JSON
+    /// File with one architecture per line which are merged with --arch, blank lines and lines starting with # are
+    /// skipped
+    #[clap(long, value_name = "FILE")]
+    pub arch_file: Option<PathBuf>,
+    /// File to write a single vendor, OS and architecture to instead of the export path, - writes to stdout
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["gzip_level", "output_layout", "by_image_type", "prune_empty", "changed_only", "list_paths"]
+    )]
+    pub output: Option<PathBuf>,
+    /// Layout of the exported files e.g.: {vendor}/{os}/{arch}.json, {vendor}/{os}-{arch}.json, {os}/{arch}/{vendor}.json,
+    /// {vendor}-{os}-{arch}.json
+    #[clap(long, value_name = "LAYOUT")]
+    pub output_layout: Option<String>,
+    /// Export the image types to separate files in form of {vendor}/{image_type}/{os}/{arch}.json
+    #[clap(long, default_value = "false", conflicts_with = "output_layout")]
+    pub by_image_type: bool,
+    /// Export at most the given number of records per file, ordered by URL
+    ///
+    /// Paged files are suffixed with the range of their records e.g. {vendor}/{os}/{arch}.20-29.json
+    #[clap(long, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..))]
+    pub limit: Option<u64>,
+    /// Number of records to skip per file before exporting e.g. to export the next page with --limit
+    #[clap(long, default_value = "0", value_name = "COUNT")]
+    pub offset: u64,
+    /// Print the paths of the files which would be exported without querying records or writing any files
     #[clap(long, default_value = "false")]
-    pub pretty: bool,
+    pub list_paths: bool,
+    /// Write the operating systems, architectures, Java versions and features of each vendor to facets.json instead
+    /// of exporting the records e.g. for the filters of a front-end
+    #[clap(long, default_value = "false", conflicts_with = "list_paths")]
+    pub group_features: bool,
+    #[clap(flatten)]
+    pub export: ExportArgs,
 }
 
 impl Vendor {
     pub fn run(self) -> Result<()> {
+        let layout = match self.by_image_type {
+            true => IMAGE_TYPE_LAYOUT.to_string(),
+            false => get_output_layout(self.output_layout, "vendor")?,
+        };
         let conf = Conf::try_get()?;
-        if conf.export.path.is_none() {
+        if conf.export.path.is_none() && self.output.is_none() {
             return Err(eyre::eyre!("export.path is not configured"));
         }
+        let export_path = PathBuf::from(conf.export.path.unwrap_or_default());
         let conn_pool = ConnectionPool::get_pool()?;
         let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
 
         let vendors_default = db.get_distinct("vendor")?;
-        let vendors = self.vendors.unwrap_or(vendors_default);
+        let vendors = get_values(self.vendors, self.vendors_file.as_deref(), "vendors")?.unwrap_or(vendors_default);
+        if self.group_features {
+            let path = self.output.unwrap_or_else(|| export_path.join(FACETS_FILE));
+            let facets = get_facets(&vendors, |column, vendor| db.get_distinct_by_vendor(column, vendor))?;
+            info!("exporting facets of {} vendors to {}", facets.len(), path.display());
+            let indent = get_indent(self.export.pretty, self.export.indent);
+            return write_output(&path, &facets, indent.as_deref(), io::stdout().lock());
+        }
 
         let oses_default = db.get_distinct("os")?;
-        let oses = self.os.unwrap_or(oses_default);
+        let musl_os = conf.export.musl_os.as_deref();
+        let oses = get_values(self.os, self.os_file.as_deref(), "os")?;
+        // musl builds are stored as linux and exported under musl_os along with the glibc builds of linux
+        let oses = split_musl_os(oses.unwrap_or(oses_default), musl_os);
 
         let arch_default = db.get_distinct("architecture")?;
-        let archs = self.arch.unwrap_or(arch_default);
-
-        let include = self.include.unwrap_or_default();
-        let exclude = self.exclude.unwrap_or_default();
-
-        let filters = get_filter_map(self.filters.unwrap_or_default());
-
-        let export_path = conf.export.path.unwrap();
-
-        for vendor in &vendors {
-            for os in &oses {
-                for arch in &archs {
-                    let data = db.export_vendor(vendor, os, arch)?;
-
-                    let export_data = data
-                        .into_par_iter()
-                        .filter(|item| JvmData::filter(item, &filters))
-                        .map(|item| JvmData::map(&item, &include, &exclude))
-                        .collect::<Vec<Map<String, Value>>>();
-                    let size = export_data.len();
-
-                    info!("exporting {size} records for {vendor}/{os}/{arch}");
-                    let path = PathBuf::from(&export_path)
-                        .join(vendor)
-                        .join(os)
-                        .join(format!("{arch}.json"));
-                    if let Some(parent) = path.parent() {
-                        std::fs::create_dir_all(parent)?;
-                    }
+        let archs = get_values(self.arch, self.arch_file.as_deref(), "arch")?.unwrap_or(arch_default);
+
+        let include_jre = includes_jre(self.export.include_jre, &self.export.filters.filter_map()?);
+        let image_types = match self.by_image_type {
+            true => db
+                .get_distinct("image_type")?
+                .into_iter()
+                .filter(|image_type| include_jre || image_type != "jre")
+                .collect(),
+            false => vec![],
+        };
+
+        let page = Page {
+            limit: self.limit,
+            offset: self.offset,
+        };
+        let targets = get_targets(&export_path, &layout, &vendors, &oses, &archs, &image_types, &page);
+        if self.output.is_some() && targets.len() != 1 {
+            return Err(eyre::eyre!(
+                "--output requires a single vendor, os and arch, got {} combinations",
+                targets.len()
+            ));
+        }
+        if self.list_paths {
+            for (_, path) in &targets {
+                println!("{}", path.display());
+            }
+            return Ok(());
+        }
+
+        let mut pipeline = Pipeline::new(self.export, export_path, conf.export.renames)?.with_page(page);
+        export_targets(
+            &targets,
+            musl_os,
+            |vendor, os, arch| db.export_vendor(vendor, os, arch),
+            |path, data| pipeline.export(self.output.clone().unwrap_or(path), data),
+        )?;
+        pipeline.finish()
+    }
+}
+
+/// Passes the records of each exported file to `export`, the records are queried by vendor, OS and architecture
+/// stored in the database and split by the OS they are exported under and the image type of the file
+fn export_targets<Q, E>(targets: &[(Target, PathBuf)], musl_os: Option<&str>, query: Q, mut export: E) -> Result<()>
+where
+    Q: Fn(&str, &str, &str) -> Result<Vec<JvmData>>,
+    E: FnMut(PathBuf, Vec<JvmData>) -> Result<()>,
+{
+    for (target, path) in targets {
+        let data = query(target.vendor, get_db_os(target.os, musl_os), target.arch)?
+            .into_iter()
+            .filter(|item| get_export_os(item, musl_os) == target.os)
+            .filter(|item| target.has_image_type(item))
+            .collect();
+        export(path.clone(), data)?;
+    }
+    Ok(())
+}
+
+/// Layout of exports split by image type
+const IMAGE_TYPE_LAYOUT: &str = "{vendor}/{image_type}/{os}/{arch}.json";
+
+/// Properties of the records of an exported file
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Target<'a> {
+    vendor: &'a str,
+    os: &'a str,
+    arch: &'a str,
+    /// Image type of the records, any if none
+    image_type: Option<&'a str>,
+}
+
+impl Target<'_> {
+    /// Returns whether the record has the image type of the file
+    fn has_image_type(&self, item: &JvmData) -> bool {
+        self.image_type.is_none_or(|image_type| item.image_type == image_type)
+    }
+}
+
+/// Returns the properties of each exported file along with its path, split by image type unless none are given
+///
+/// The paths of a page are suffixed with the range of its records.
+fn get_targets<'a>(
+    root: &Path,
+    layout: &str,
+    vendors: &'a [String],
+    oses: &'a [String],
+    archs: &'a [String],
+    image_types: &'a [String],
+    page: &Page,
+) -> Vec<(Target<'a>, PathBuf)> {
+    let image_types = match image_types.is_empty() {
+        true => vec![None],
+        false => image_types.iter().map(|image_type| Some(image_type.as_str())).collect(),
+    };
+    let mut targets = Vec::new();
+    for vendor in vendors {
+        for image_type in &image_types {
+            for os in oses {
+                for arch in archs {
+                    let values = [
+                        ("vendor", vendor.as_str()),
+                        ("image_type", image_type.unwrap_or_default()),
+                        ("os", os.as_str()),
+                        ("arch", arch.as_str()),
+                    ];
+                    let target = Target {
+                        vendor,
+                        os,
+                        arch,
+                        image_type: *image_type,
+                    };
+                    let path = get_export_path(root, layout, &values);
+                    targets.push((target, get_page_path(&path, page)));
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// Returns the path of a page of an export file suffixed with the range of its records e.g. `linux/x86_64.20-29.json`,
+/// the path itself if all records are exported
+fn get_page_path(path: &Path, page: &Page) -> PathBuf {
+    let range = match (page.limit, page.offset) {
+        (None, 0) => return path.to_path_buf(),
+        (None, offset) => format!("{offset}-"),
+        (Some(limit), offset) => format!("{offset}-{}", offset.saturating_add(limit) - 1),
+    };
+    path.with_extension(format!("{range}.json"))
+}
+
+/// Returns the facets of each vendor from the distinct values of a column of the records of a vendor
+///
+/// Features are stored comma separated, so the distinct combinations are split into single features.
+fn get_facets<F>(vendors: &[String], distinct: F) -> Result<BTreeMap<String, Facets>>
+where
+    F: Fn(&str, &str) -> Result<Vec<String>>,
+{
+    let mut result = BTreeMap::new();
+    for vendor in vendors {
+        let mut java_version = distinct("java_version", vendor)?;
+        java_version.sort_by_key(|version| Versioning::new(version));
+        let features = distinct("features", vendor)?
+            .iter()
+            .flat_map(|features| features.split(','))
+            .filter(|feature| !feature.is_empty())
+            .map(String::from)
+            .collect::<BTreeSet<_>>();
+        let facets = Facets {
+            os: distinct("os", vendor)?,
+            architecture: distinct("architecture", vendor)?,
+            java_version,
+            features: features.into_iter().collect(),
+        };
+        result.insert(vendor.clone(), facets);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
 
-                    let file = File::create(path)?;
-                    match self.pretty {
-                        true => serde_json::to_writer_pretty(file, &export_data)?,
-                        false => serde_json::to_writer(file, &export_data)?,
+    use serde_json::Value;
+
+    use crate::{cli::export::tests::export_args, test_util::TempDir};
+
+    use super::*;
+
+    const MUSL_OS: Option<&str> = Some("linux-musl");
+
+    /// Returns JDK and JRE records of temurin on x86_64 as stored in the database, musl builds are stored as linux
+    fn get_records() -> Vec<JvmData> {
+        let mut records = Vec::new();
+        for (os, libc) in [("linux", "glibc"), ("linux", "musl"), ("macosx", "glibc")] {
+            for image_type in ["jdk", "jre"] {
+                for n in 0..3 {
+                    records.push(JvmData {
+                        architecture: "x86_64".to_string(),
+                        features: (libc == "musl").then(|| vec![libc.to_string()]),
+                        image_type: image_type.to_string(),
+                        os: os.to_string(),
+                        url: format!("https://example.com/temurin-21.0.{n}-{os}-{libc}-{image_type}.tar.gz"),
+                        vendor: "temurin".to_string(),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        records
+    }
+
+    /// Exports the records like `run` and returns the records written to each target path
+    fn export(
+        root: &Path,
+        records: &[JvmData],
+        args: &[&str],
+        image_types: &[String],
+        page: Page,
+    ) -> Vec<(PathBuf, Vec<Value>)> {
+        let vendors = ["temurin".to_string()];
+        let oses = split_musl_os(vec!["linux".to_string(), "macosx".to_string()], MUSL_OS);
+        let archs = ["x86_64".to_string()];
+        let layout = match image_types.is_empty() {
+            true => "{vendor}/{os}/{arch}.json",
+            false => IMAGE_TYPE_LAYOUT,
+        };
+        let targets = get_targets(root, layout, &vendors, &oses, &archs, image_types, &page);
+        let mut pipeline = Pipeline::new(export_args(args), root.to_path_buf(), None)
+            .unwrap()
+            .with_page(page);
+        // ordered by URL like the query of the repository
+        let query = |vendor: &str, os: &str, arch: &str| {
+            let mut data = records
+                .iter()
+                .filter(|item| item.vendor == vendor && item.os == os && item.architecture == arch)
+                .cloned()
+                .collect::<Vec<_>>();
+            data.sort_by(|a, b| a.url.cmp(&b.url));
+            Ok(data)
+        };
+        export_targets(&targets, MUSL_OS, query, |path, data| pipeline.export(path, data)).unwrap();
+        pipeline.finish().unwrap();
+        targets
+            .into_iter()
+            .map(|(_, path)| {
+                let data = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+                (path, data)
+            })
+            .collect()
+    }
+
+    /// Returns the files below the directory
+    fn list_files(dir: &Path) -> BTreeSet<PathBuf> {
+        let mut files = BTreeSet::new();
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            match path.is_dir() {
+                true => files.extend(list_files(&path)),
+                false => {
+                    files.insert(path);
+                }
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn test_list_paths_match_export() {
+        let records = get_records();
+        let image_types = ["jdk", "jre"].map(String::from);
+        let page = Page {
+            limit: Some(2),
+            offset: 0,
+        };
+        for (image_types, page) in [
+            (&[][..], Page::default()),
+            (&image_types[..], Page::default()),
+            (&image_types, page),
+        ] {
+            let dir = TempDir::new("list-paths");
+            let exported = export(&dir, &records, &["--include-jre"], image_types, page);
+            // the paths --list-paths prints are the files written by the export
+            let listed = exported.iter().map(|(path, _)| path.clone()).collect::<BTreeSet<_>>();
+            assert_eq!(list_files(&dir), listed, "{image_types:?} {page:?}");
+            for (path, data) in &exported {
+                let path = path.strip_prefix(&*dir).unwrap().to_str().unwrap();
+                for item in data {
+                    let os = match item["features"].as_array().is_some_and(|f| f.contains(&"musl".into())) {
+                        true => "linux-musl",
+                        false => item["os"].as_str().unwrap(),
+                    };
+                    assert!(path.contains(&format!("/{os}/")), "{path} {item}");
+                    if !image_types.is_empty() {
+                        let image_type = item["image_type"].as_str().unwrap();
+                        assert!(path.contains(&format!("/{image_type}/")), "{path} {item}");
                     }
                 }
             }
         }
-        Ok(())
+    }
+
+    #[test]
+    fn test_export_pages() {
+        let records = get_records();
+        for args in [&[][..], &["--filters", "image_type=jre"], &["--include-jre"]] {
+            let dir = TempDir::new("export-pages");
+            let unpaged = export(&dir.join("all"), &records, args, &[], Page::default());
+            let pages = [0, 2, 4].map(|offset| {
+                let page = Page { limit: Some(2), offset };
+                export(&dir.join("pages"), &records, args, &[], page)
+            });
+            for (i, (path, data)) in unpaged.iter().enumerate() {
+                assert!(!data.is_empty(), "{}", path.display());
+                let concatenated = pages.iter().flat_map(|page| page[i].1.clone()).collect::<Vec<_>>();
+                assert_eq!(&concatenated, data, "{args:?} {}", path.display());
+                assert!(pages.iter().all(|page| page[i].1.len() <= 2));
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_targets() {
+        let root = Path::new("public/api/jvm");
+        let vendors = ["temurin", "zulu"].map(String::from);
+        let oses = ["linux".to_string()];
+        let archs = ["aarch64", "x86_64"].map(String::from);
+
+        let targets = get_targets(
+            root,
+            "{vendor}/{os}/{arch}.json",
+            &vendors,
+            &oses,
+            &archs,
+            &[],
+            &Page::default(),
+        );
+        let paths = targets.iter().map(|(_, path)| path.clone()).collect::<Vec<_>>();
+        let expected = [
+            "public/api/jvm/temurin/linux/aarch64.json",
+            "public/api/jvm/temurin/linux/x86_64.json",
+            "public/api/jvm/zulu/linux/aarch64.json",
+            "public/api/jvm/zulu/linux/x86_64.json",
+        ]
+        .map(PathBuf::from);
+        assert_eq!(paths, expected);
+        assert_eq!(
+            targets[1].0,
+            Target {
+                vendor: "temurin",
+                os: "linux",
+                arch: "x86_64",
+                image_type: None
+            }
+        );
+
+        let targets = get_targets(
+            root,
+            "{os}/{arch}/{vendor}.json",
+            &vendors,
+            &oses,
+            &archs,
+            &[],
+            &Page::default(),
+        );
+        assert_eq!(targets[0].1, PathBuf::from("public/api/jvm/linux/aarch64/temurin.json"));
+    }
+
+    #[test]
+    fn test_get_facets() {
+        let records = [
+            ("temurin", "linux", "x86_64", "21.0.1", None),
+            ("temurin", "linux", "aarch64", "8.0.392", None),
+            ("temurin", "macosx", "aarch64", "17.0.9", None),
+            ("zulu", "linux", "x86_64", "21.0.1", Some("crac,musl")),
+            ("zulu", "windows", "x86_64", "21.0.1", Some("javafx")),
+            ("zulu", "linux", "x86_64", "17.0.9", Some("musl")),
+        ];
+        // emulates the distinct query of the repository on the seeded records
+        let distinct = |column: &str, vendor: &str| -> Result<Vec<String>> {
+            let values = records
+                .iter()
+                .filter(|record| record.0 == vendor)
+                .filter_map(|record| match column {
+                    "os" => Some(record.1.to_string()),
+                    "architecture" => Some(record.2.to_string()),
+                    "java_version" => Some(record.3.to_string()),
+                    "features" => record.4.map(String::from),
+                    _ => None,
+                })
+                .collect::<BTreeSet<_>>();
+            Ok(values.into_iter().collect())
+        };
+
+        let vendors = ["temurin", "zulu"].map(String::from);
+        let facets = get_facets(&vendors, distinct).unwrap();
+        assert_eq!(
+            facets["temurin"],
+            Facets {
+                os: vec!["linux".to_string(), "macosx".to_string()],
+                architecture: vec!["aarch64".to_string(), "x86_64".to_string()],
+                java_version: vec!["8.0.392".to_string(), "17.0.9".to_string(), "21.0.1".to_string()],
+                features: vec![],
+            }
+        );
+        assert_eq!(facets["zulu"].os, vec!["linux", "windows"]);
+        assert_eq!(facets["zulu"].architecture, vec!["x86_64"]);
+        assert_eq!(facets["zulu"].java_version, vec!["17.0.9", "21.0.1"]);
+        assert_eq!(facets["zulu"].features, vec!["crac", "javafx", "musl"]);
+    }
+
+    #[test]
+    fn test_get_page_path() {
+        let path = Path::new("public/api/jvm/temurin/linux/x86_64.json");
+        assert_eq!(get_page_path(path, &Page::default()), path);
+        let pages = [(Some(10), 0), (Some(10), 20), (None, 20)].map(|(limit, offset)| Page { limit, offset });
+        let paths = pages.map(|page| get_page_path(path, &page));
+        assert_eq!(
+            paths,
+            [
+                "public/api/jvm/temurin/linux/x86_64.0-9.json",
+                "public/api/jvm/temurin/linux/x86_64.20-29.json",
+                "public/api/jvm/temurin/linux/x86_64.20-.json",
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_get_targets_from_files() {
+        let root = Path::new("public/api/jvm");
+        let dir = TempDir::new("vendors");
+        let path = dir.join("vendors.txt");
+        fs::write(&path, "# curated vendors\ntemurin\n\n  zulu  \n").unwrap();
+        let from_file = get_values(None, Some(&path), "vendors").unwrap().unwrap();
+        let inline = get_values(Some(vec!["temurin".to_string(), "zulu".to_string()]), None, "vendors")
+            .unwrap()
+            .unwrap();
+        assert_eq!(from_file, inline);
+        let oses = ["linux".to_string()];
+        let archs = ["x86_64".to_string()];
+        assert_eq!(
+            get_targets(
+                root,
+                "{vendor}/{os}/{arch}.json",
+                &from_file,
+                &oses,
+                &archs,
+                &[],
+                &Page::default()
+            ),
+            get_targets(
+                root,
+                "{vendor}/{os}/{arch}.json",
+                &inline,
+                &oses,
+                &archs,
+                &[],
+                &Page::default()
+            )
+        );
+
+        // vendors of the file are merged with the given ones
+        let merged = get_values(
+            Some(vec!["zulu".to_string(), "corretto".to_string()]),
+            Some(&path),
+            "vendors",
+        );
+        assert_eq!(merged.unwrap().unwrap(), vec!["zulu", "corretto", "temurin"]);
+        assert_eq!(get_values(None, None, "vendors").unwrap(), None);
+
+        // a file with only comments and blank lines would export nothing
+        fs::write(&path, "# curated vendors\n\n").unwrap();
+        assert!(get_values(None, Some(&path), "vendors").is_err());
+        let inline = get_values(Some(vec!["zulu".to_string()]), Some(&path), "vendors");
+        assert_eq!(inline.unwrap().unwrap(), vec!["zulu"]);
+
+        fs::remove_file(&path).unwrap();
+        assert!(get_values(None, Some(&path), "vendors").is_err());
+    }
+
+    #[test]
+    fn test_get_targets_by_image_type() {
+        let root = Path::new("public/api/jvm");
+        let vendors = ["temurin".to_string()];
+        let oses = ["linux".to_string()];
+        let archs = ["x86_64".to_string()];
+        let image_types = ["jdk", "jre"].map(String::from);
+        let targets = get_targets(
+            root,
+            IMAGE_TYPE_LAYOUT,
+            &vendors,
+            &oses,
+            &archs,
+            &image_types,
+            &Page::default(),
+        );
+
+        let records = ["jdk", "jre"].map(|image_type| JvmData {
+            image_type: image_type.to_string(),
+            url: format!("https://example.com/temurin-21-{image_type}.tar.gz"),
+            ..Default::default()
+        });
+        let paths = records
+            .iter()
+            .map(|item| {
+                let files = targets
+                    .iter()
+                    .filter(|(target, _)| target.has_image_type(item))
+                    .map(|(_, path)| path.clone())
+                    .collect::<Vec<_>>();
+                assert_eq!(files.len(), 1, "{}", item.image_type);
+                files[0].clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "public/api/jvm/temurin/jdk/linux/x86_64.json",
+                "public/api/jvm/temurin/jre/linux/x86_64.json",
+            ]
+            .map(PathBuf::from)
+        );
     }
 }
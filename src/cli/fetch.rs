@@ -1,87 +1,386 @@
-use crossbeam_channel::{select, unbounded};
 use eyre::Result;
-use log::{error, info};
-use std::{collections::HashMap, sync::Arc};
+use itertools::Itertools;
+use log::{debug, error, info, warn};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 use crate::{
-    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
-    jvm::vendor::{VENDORS, Vendor},
+    cli::errors::ErrorArgs,
+    config::Conf,
+    db::{
+        jvm_repository::JvmRepository,
+        pool::ConnectionPool,
+        vendor_run_repository::{VendorRun, VendorRunRepository},
+    },
+    env,
+    http::{Client, DEFAULT_TIMEOUT_PER_URL, default_user_agent},
+    jvm::{
+        JvmData,
+        vendor::{
+            Checkpoint, FetchOptions, OPTIONAL_FEATURES, Overrides, VENDORS, Vendor, VersionRange,
+            parse_checksum_sidecar, sample,
+        },
+    },
 };
 
 /// Fetch data from JVM vendors
 ///
-/// Will crawl data from all vendors if none are specified
-#[derive(Debug, clap::Args)]
+/// Will crawl data from the vendors in fetch.default_vendors or all vendors if none are specified.
+/// Exits with 0 if all vendors were fetched and stored, or with 1 if one or more vendors failed unless --ignore-errors
+/// is set. With --fail-fast no further vendors are started once one failed.
+#[derive(Debug, Default, clap::Args)]
 #[clap(verbatim_doc_comment)]
 pub struct Fetch {
     /// Vendors to fetch e.g.: openjdk, zulu
     #[clap(value_name = "VENDOR")]
     pub vendors: Vec<String>,
+    /// Vendor groups to fetch as configured in fetch.vendor_groups e.g.: lts
+    #[clap(short = 'g', long, num_args = 0.., value_delimiter = ',', value_name = "GROUP")]
+    pub vendor_group: Vec<String>,
+    /// Fetch the vendors which already have records in the database, e.g. to refresh an earlier crawl
+    #[clap(long, default_value = "false", conflicts_with_all = ["vendors", "vendor_group"])]
+    pub vendors_from_db: bool,
+    /// Fetch a single version of the vendor instead of all versions e.g. to patch a release: roast fetch zulu --version
+    /// 21.30.19.0
+    ///
+    /// Only supported by vendors whose API can be queried by version: zulu
+    #[clap(
+        long,
+        value_name = "VERSION",
+        requires = "vendors",
+        conflicts_with_all = ["vendor_group", "vendors_from_db", "min_version", "max_version", "refresh_checksums_only"]
+    )]
+    pub version: Option<String>,
+    /// Lowest major version to fetch for vendors crawling per-version archives e.g.: 21
+    #[clap(long, value_name = "VERSION")]
+    pub min_version: Option<u32>,
+    /// Highest major version to fetch for vendors crawling per-version archives e.g.: 23
+    #[clap(long, value_name = "VERSION")]
+    pub max_version: Option<u32>,
+    /// Store the final URL of redirected downloads as resolved_url, the URL given by the vendor is kept
+    #[clap(long, default_value = "false")]
+    pub resolve_redirects: bool,
+    /// Store builds for operating systems other than linux, macosx and windows e.g.: aix, solaris, zos
+    #[clap(long, default_value = "false")]
+    pub include_unsupported_os: bool,
+    /// File to record finished vendors and versions in, an interrupted fetch skips them when started again with the same
+    /// file
+    ///
+    /// The file is removed once all vendors were fetched successfully.
+    #[clap(long, value_name = "FILE")]
+    pub resume_token: Option<PathBuf>,
+    /// Re-fetch the checksum_url of existing records and update their checksum without crawling vendors
+    #[clap(long, default_value = "false", conflicts_with_all = ["resolve_redirects", "min_version", "max_version"])]
+    pub refresh_checksums_only: bool,
+    /// Fetch at most the given number of release pages of vendors publishing on GitHub
+    ///
+    /// Only the most recent releases are fetched as GitHub lists the newest releases first.
+    #[clap(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..))]
+    pub pages: Option<u32>,
+    /// User agent sent instead of the default one, overrides fetch.user_agent of the configuration
+    #[clap(long, value_name = "AGENT")]
+    pub user_agent: Option<String>,
+    /// Seconds a single request may take until it is aborted, overrides fetch.timeout_per_url of the configuration
+    #[clap(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout_per_url: Option<u64>,
+    /// Store release candidate, beta and alpha builds as ea releases with the prerelease feature or drop them,
+    /// overrides fetch.prereleases of the configuration
+    #[clap(long, value_name = "MODE", value_parser = ["store", "drop"])]
+    pub prereleases: Option<String>,
+    /// Fail a vendor if one of its scraped pages contains no matching links or a record has an invalid version instead
+    /// of warning
+    #[clap(long, default_value = "false")]
+    pub strict: bool,
+    /// Store at most the given number of records per vendor e.g. for smoke testing a parser
+    #[clap(long, value_name = "COUNT")]
+    pub max_records: Option<usize>,
+    /// Seed for choosing the records kept by --max-records and the URLs checked by fetch.url_check_sample_rate
+    ///
+    /// The same seed chooses the same records on each run. Without a seed --max-records keeps the first records by
+    /// URL and the checked URLs are chosen at random.
+    #[clap(long, value_name = "SEED")]
+    pub seed: Option<u64>,
+    /// Save the body of each fetched response to the directory, named by its URL, e.g. to debug a parser
+    #[clap(long, value_name = "DIR")]
+    pub save_raw: Option<PathBuf>,
+    /// Crawl from the responses saved with --save-raw to the directory instead of the network, e.g. to test parsers
+    #[clap(long, value_name = "DIR", conflicts_with_all = ["save_raw", "resolve_redirects"])]
+    pub offline: Option<PathBuf>,
+    /// Fetch one vendor at a time on a single thread so the logs of vendors are not interleaved e.g. for debugging
+    #[clap(long, default_value = "false")]
+    pub serial: bool,
+    /// Print a summary of the results of each vendor as JSON to stdout
+    #[clap(long, default_value = "false")]
+    pub json: bool,
+    #[clap(flatten)]
+    pub errors: ErrorArgs,
 }
 
 impl Fetch {
-    pub fn run(self) -> Result<()> {
+    pub fn run(mut self) -> Result<()> {
+        if self.version.is_some() && self.vendors.len() != 1 {
+            return Err(eyre::eyre!("--version requires a single vendor"));
+        }
+        let conf = Conf::try_get()?;
+        let vendor_groups = conf.fetch.vendor_groups.unwrap_or_default();
+        self.vendors = expand_vendor_groups(&self.vendors, &self.vendor_group, &vendor_groups)?;
+        self.vendors = with_default_vendors(self.vendors, conf.fetch.default_vendors);
+        if self.vendors_from_db {
+            let db = JvmRepository::new(ConnectionPool::get_pool()?)?;
+            self.vendors = supported_vendors(db.get_distinct("vendor")?);
+            if self.vendors.is_empty() {
+                return Err(eyre::eyre!("no records of supported vendors in the database"));
+            }
+        }
+        let priorities = conf.fetch.priorities.unwrap_or_default();
+        let user_agent = self
+            .user_agent
+            .clone()
+            .or(conf.fetch.user_agent)
+            .unwrap_or_else(|| default_user_agent(&env::BINARY_NAME));
+        if user_agent.trim().is_empty() {
+            warn!("sending an empty user agent, some hosts reject such requests");
+        }
+        let timeout = match self.timeout_per_url.or(conf.fetch.timeout_per_url) {
+            Some(0) => return Err(eyre::eyre!("fetch.timeout_per_url must be at least 1 second")),
+            Some(timeout) => Duration::from_secs(timeout),
+            None => DEFAULT_TIMEOUT_PER_URL,
+        };
+        let mut http = Client::new(timeout, &user_agent)?;
+        if let Some(dir) = &self.save_raw {
+            fs::create_dir_all(dir)?;
+            http = http.with_raw_dir(dir.clone());
+        }
+        if let Some(dir) = &self.offline {
+            if !dir.is_dir() {
+                return Err(eyre::eyre!("offline directory {} does not exist", dir.display()));
+            }
+            http = http.with_offline_dir(dir.clone());
+        }
+        let disabled_features = conf.fetch.disabled_features.unwrap_or_default();
+        if let Some(feature) = disabled_features
+            .iter()
+            .find(|f| !OPTIONAL_FEATURES.contains(&f.as_str()))
+        {
+            return Err(eyre::eyre!(
+                "feature {feature} can not be disabled, expected one of: {}",
+                OPTIONAL_FEATURES.join(", ")
+            ));
+        }
+        let sample_rate = conf.fetch.url_check_sample_rate.unwrap_or_default();
+        if !(0.0..=1.0).contains(&sample_rate) {
+            return Err(eyre::eyre!("fetch.url_check_sample_rate must be between 0 and 1"));
+        }
+        let mut options = FetchOptions::new(http);
+        options.version_range = VersionRange {
+            min: self.min_version,
+            max: self.max_version,
+        };
+        options.disabled_features = disabled_features;
+        options.image_types = conf.fetch.image_types.unwrap_or_default();
+        options.include_unsupported_os = self.include_unsupported_os;
+        // downloads can not be checked offline
+        options.url_check_sample_rate = if self.offline.is_some() { 0.0 } else { sample_rate };
+        options.sample_seed = self.seed;
+        options.strict = self.strict;
+        if let Some(prereleases) = self.prereleases.as_ref().or(conf.fetch.prereleases.as_ref()) {
+            options.prereleases = prereleases.parse()?;
+        }
+        options.cookies = conf.fetch.cookies.clone().unwrap_or_default().into_iter().collect();
+        options.overrides = Overrides::new(
+            conf.normalize.os.unwrap_or_default(),
+            conf.normalize.architecture.unwrap_or_default(),
+        );
+        if let Some(channel) = &conf.fetch.temurin_channel {
+            options.temurin_channel = channel.parse()?;
+        }
+        options.max_pages = self.pages.map(|pages| pages as usize);
+        if let Some(jitter) = conf.fetch.retry_jitter {
+            options.retry_jitter = jitter.max(0.0);
+        }
+        if let Some(budget) = conf.fetch.retry_budget {
+            options.retry_budget = budget;
+        }
+
         if self.vendors.is_empty() {
             info!("fetching all vendors");
         } else {
             info!("fetching vendors: {:?}", self.vendors);
         }
 
+        if self.refresh_checksums_only {
+            return self.refresh_checksums(&options.http);
+        }
+
         let start = std::time::Instant::now();
         let conn_pool = ConnectionPool::get_pool()?;
-        let pool = rayon::ThreadPoolBuilder::default().build()?;
-        pool.scope(|s| {
-            let run = |name: String, vendor: Arc<dyn Vendor>| {
-                let conn_pool = conn_pool.clone();
-                s.spawn(move |_| {
-                    let db = match JvmRepository::new(conn_pool) {
-                        Ok(db) => db,
-                        Err(err) => {
-                            error!("[{name}] failed to connect to database: {err}");
-                            return;
-                        }
-                    };
-
-                    info!("[{name}] fetching meta data");
-                    let jvm_data = match vendor.fetch() {
-                        Ok(data) => data,
-                        Err(err) => {
-                            error!("[{name}] failed to fetch meta data: {err}");
-                            return;
-                        }
-                    };
-
-                    info!("[{name}] writing to database");
-                    match db.insert(&jvm_data) {
-                        Ok(result) => {
-                            info!("[{name}] inserted/modified {result} records")
-                        }
-                        Err(err) => {
-                            error!("[{name}] failed to write to database: {err}");
-                        }
-                    };
-                });
+        let pool = build_pool(self.serial)?;
+        let resolve_redirects = self.resolve_redirects;
+        let max_records = self.max_records;
+        let seed = self.seed;
+        let version = self.version.as_deref();
+        let stats = Arc::new(FetchStats::default());
+        let errors = self.errors.policy();
+        if let Some(path) = &self.resume_token {
+            let checkpoint = Checkpoint::load(path)?;
+            let done = checkpoint.done();
+            if !done.is_empty() {
+                info!("resuming fetch, skipping vendors: {done:?}");
+            }
+            options.checkpoint = Some(checkpoint);
+        }
+        let vendors = schedule(self.get_vendors(), &priorities)
+            .into_iter()
+            .filter(|(name, _)| {
+                let done = options.checkpoint.as_ref().is_some_and(|c| c.is_done(name));
+                if done {
+                    debug!("[{name}] skipping vendor fetched before");
+                }
+                !done
+            })
+            .collect();
+        run_vendors(&pool, vendors, |name, vendor| {
+            if errors.aborts() && stats.errored() > 0 {
+                warn!("[{name}] skipping vendor as another vendor failed");
+                return;
+            }
+            let db = match JvmRepository::new(conn_pool.clone()) {
+                Ok(db) => db,
+                Err(err) => {
+                    error!("[{name}] failed to connect to database: {err}");
+                    stats.add_errored(1);
+                    stats.add_run(VendorRun::failure(&name, Duration::ZERO, 0, &err));
+                    return;
+                }
+            };
+
+            let runs = match VendorRunRepository::new(conn_pool.clone()) {
+                Ok(runs) => runs,
+                Err(err) => {
+                    error!("[{name}] failed to connect to database: {err}");
+                    stats.add_errored(1);
+                    stats.add_run(VendorRun::failure(&name, Duration::ZERO, 0, &err));
+                    return;
+                }
+            };
+            let save_run = |run: VendorRun| {
+                let run = run.with_pages(options.pages(&name));
+                if let Err(err) = runs.save(&run) {
+                    error!("[{name}] failed to save run: {err}");
+                }
+                stats.add_run(run);
             };
 
-            let (tx, rx) = unbounded();
-            for (name, vendor) in self.get_vendors() {
-                tx.send((name, vendor)).unwrap();
+            info!("[{name}] fetching meta data");
+            let start = std::time::Instant::now();
+            let fetched = match version {
+                Some(version) => vendor.fetch_one(&options, version),
+                None => vendor.fetch(&options),
+            };
+            if let Some(pages) = options.pages(&name) {
+                info!("[{name}] fetched {pages} release pages");
             }
-            drop(tx);
-
-            loop {
-                select! {
-                    recv(rx) -> msg => {
-                        match msg {
-                            Ok((name, vendor)) => run(name, vendor),
-                            Err(_) => break,
-                        }
-                    }
+            let mut jvm_data = match fetched {
+                Ok(data) => data,
+                Err(err) => {
+                    error!("[{name}] failed to fetch meta data: {err}");
+                    stats.add_errored(1);
+                    save_run(VendorRun::failure(&name, start.elapsed(), 0, &err));
+                    return;
                 }
+            };
+
+            if let Some(max_records) = max_records {
+                jvm_data = limit_records(jvm_data, max_records, seed);
+                info!("[{name}] limited to {} records", jvm_data.len());
             }
+
+            if resolve_redirects {
+                info!("[{name}] resolving redirects");
+                jvm_data = resolve_redirects_of(&options.http, &name, jvm_data);
+            }
+
+            stats.add_discovered(jvm_data.len() as u64);
+            info!("[{name}] writing to database");
+            match db.insert(&jvm_data) {
+                Ok(result) => {
+                    info!("[{name}] inserted/modified {result} records");
+                    stats.add_inserted(result);
+                    save_run(VendorRun::success(&name, start.elapsed(), jvm_data.len(), result));
+                    if let Some(checkpoint) = &options.checkpoint
+                        && let Err(err) = checkpoint.mark_done(&name)
+                    {
+                        error!("[{name}] failed to write resume token: {err}");
+                    }
+                }
+                Err(err) => {
+                    error!("[{name}] failed to write to database: {err}");
+                    stats.add_errored(1);
+                    save_run(VendorRun::failure(&name, start.elapsed(), jvm_data.len(), &err));
+                }
+            };
         });
 
-        info!("fetched all vendors in {:.2} seconds", start.elapsed().as_secs_f32());
+        info!(
+            "fetched all vendors in {:.2} seconds: {} discovered, {} inserted/modified, {} errored",
+            start.elapsed().as_secs_f32(),
+            stats.discovered(),
+            stats.inserted(),
+            stats.errored()
+        );
+        if let Some(checkpoint) = options.checkpoint
+            && stats.errored() == 0
+        {
+            checkpoint.finish()?;
+        }
+        let summary = stats.summary(start.elapsed());
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        errors.finish(summary.result())
+    }
+
+    fn refresh_checksums(&self, http: &Client) -> Result<()> {
+        let start = std::time::Instant::now();
+        let db = JvmRepository::new(ConnectionPool::get_pool()?)?;
+        let records = db.get_checksum_urls(&self.vendors)?;
+        info!("refreshing checksums of {} records", records.len());
+        let changed = records
+            .into_par_iter()
+            .filter_map(|mut data| {
+                let checksum_url = data.checksum_url.clone()?;
+                match http.get_text(&checksum_url) {
+                    Ok(body) => {
+                        let refreshed = refresh_checksum(&data.url, &checksum_url, data.checksum.as_deref(), &body)?;
+                        data.replace_checksum(refreshed);
+                        Some(data)
+                    }
+                    Err(err) => {
+                        warn!("failed to fetch checksum {checksum_url}: {err}");
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut result = 0;
+        for data in changed {
+            debug!("updating checksum of {} to {:?}", data.url, data.checksum);
+            result += db.update_checksums(&data.url, data.checksum.as_deref(), &data.checksums)?;
+        }
+        info!(
+            "updated {result} checksums in {:.2} seconds",
+            start.elapsed().as_secs_f32()
+        );
         Ok(())
     }
 
@@ -93,3 +392,546 @@ impl Fetch {
             .collect()
     }
 }
+
+/// Returns the pool fetching the vendors, a single thread if serial or one per CPU or RAYON_NUM_THREADS otherwise
+fn build_pool(serial: bool) -> Result<rayon::ThreadPool> {
+    let num_threads = match serial {
+        true => 1,
+        false => 0,
+    };
+    Ok(rayon::ThreadPoolBuilder::new().num_threads(num_threads).build()?)
+}
+
+/// Runs each vendor as a task of the pool, tasks are started in the order of the vendors
+///
+/// The parallel iterators of the vendors run on the pool as well, so a single thread fetches one vendor at a time.
+fn run_vendors<F>(pool: &rayon::ThreadPool, vendors: Vec<(String, Arc<dyn Vendor>)>, run: F)
+where
+    F: Fn(String, Arc<dyn Vendor>) + Sync,
+{
+    pool.scope_fifo(|s| {
+        for (name, vendor) in vendors {
+            let run = &run;
+            s.spawn_fifo(move |_| run(name, vendor));
+        }
+    });
+}
+
+/// Counters and vendor runs shared by the vendor threads of a fetch
+#[derive(Debug, Default)]
+struct FetchStats {
+    discovered: AtomicU64,
+    inserted: AtomicU64,
+    errored: AtomicU64,
+    runs: Mutex<Vec<VendorRun>>,
+}
+
+/// Results of a fetch as printed by --json
+#[derive(Debug, Serialize)]
+struct FetchSummary {
+    /// Whether all vendors were fetched and stored
+    ok: bool,
+    duration_ms: u128,
+    discovered: u64,
+    inserted: u64,
+    errored: u64,
+    vendors: BTreeMap<String, VendorRun>,
+}
+
+impl FetchSummary {
+    /// Fails if one or more vendors failed so the fetch exits with a nonzero code
+    fn result(&self) -> Result<()> {
+        match self.ok {
+            true => Ok(()),
+            false if self.errored == 1 => Err(eyre::eyre!("1 vendor failed to fetch")),
+            false => Err(eyre::eyre!("{} vendors failed to fetch", self.errored)),
+        }
+    }
+}
+
+impl FetchStats {
+    fn add_discovered(&self, count: u64) {
+        self.discovered.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn add_inserted(&self, count: u64) {
+        self.inserted.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn add_errored(&self, count: u64) {
+        self.errored.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn add_run(&self, run: VendorRun) {
+        self.runs.lock().unwrap().push(run);
+    }
+
+    fn summary(&self, duration: Duration) -> FetchSummary {
+        FetchSummary {
+            ok: self.errored() == 0,
+            duration_ms: duration.as_millis(),
+            discovered: self.discovered(),
+            inserted: self.inserted(),
+            errored: self.errored(),
+            vendors: self
+                .runs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|run| (run.vendor.clone(), run.clone()))
+                .collect(),
+        }
+    }
+
+    fn discovered(&self) -> u64 {
+        self.discovered.load(Ordering::Relaxed)
+    }
+
+    fn inserted(&self) -> u64 {
+        self.inserted.load(Ordering::Relaxed)
+    }
+
+    fn errored(&self) -> u64 {
+        self.errored.load(Ordering::Relaxed)
+    }
+}
+
+/// Records the final URL of redirected URLs as `resolved_url`, the URL itself identifies the record and is kept
+fn resolve_redirects_of(http: &Client, name: &str, jvm_data: HashSet<JvmData>) -> HashSet<JvmData> {
+    jvm_data
+        .into_par_iter()
+        .map(|mut item| {
+            match http.resolve_url(&item.url) {
+                Ok(url) if url != item.url => {
+                    debug!("[{name}] resolved {} to {url}", item.url);
+                    item.resolved_url = Some(url);
+                }
+                Ok(_) => {}
+                Err(err) => warn!("[{name}] failed to resolve {}: {err}", item.url),
+            }
+            item
+        })
+        .collect()
+}
+
+/// Returns `max` records, chosen by the seed or the first ones ordered by URL, so repeated runs keep the same records
+fn limit_records(jvm_data: HashSet<JvmData>, max: usize, seed: Option<u64>) -> HashSet<JvmData> {
+    if jvm_data.len() <= max {
+        return jvm_data;
+    }
+    let mut records = jvm_data.into_iter().collect::<Vec<_>>();
+    records.sort_by(|a, b| a.url.cmp(&b.url));
+    match seed {
+        Some(seed) => sample(&records, max, Some(seed)).into_iter().cloned().collect(),
+        None => {
+            records.truncate(max);
+            records.into_iter().collect()
+        }
+    }
+}
+
+/// Returns the checksum parsed from a sidecar file if it differs from the stored one
+///
+/// The algorithm is taken from the stored checksum, or guessed from the extension of the checksum_url
+fn refresh_checksum(url: &str, checksum_url: &str, stored: Option<&str>, body: &str) -> Option<String> {
+    let algorithm = stored
+        .and_then(|checksum| checksum.split_once(':'))
+        .map(|(algorithm, _)| algorithm.to_string())
+        .or_else(|| {
+            ["md5", "sha1", "sha256", "sha512"]
+                .into_iter()
+                .find(|algorithm| checksum_url.to_lowercase().contains(&format!(".{algorithm}")))
+                .map(String::from)
+        })?;
+    let filename = url.rsplit('/').next().unwrap_or(url);
+    let checksum = format!("{algorithm}:{}", parse_checksum_sidecar(body, filename)?);
+    match stored == Some(checksum.as_str()) {
+        true => None,
+        false => Some(checksum),
+    }
+}
+
+/// Returns the vendors extended by the vendors of the given groups
+fn expand_vendor_groups(
+    vendors: &[String],
+    groups: &[String],
+    vendor_groups: &HashMap<String, Vec<String>>,
+) -> Result<Vec<String>> {
+    let mut result = vendors.to_vec();
+    for group in groups {
+        let group_vendors = vendor_groups
+            .get(group)
+            .ok_or_else(|| eyre::eyre!("vendor group {group} is not configured"))?;
+        result.extend(group_vendors.iter().cloned());
+    }
+    result.sort();
+    result.dedup();
+    Ok(result)
+}
+
+/// Returns the stored vendors which can be fetched, records of removed or renamed vendors are skipped
+fn supported_vendors(stored: Vec<String>) -> Vec<String> {
+    let supported = VENDORS.iter().map(|v| v.get_name()).collect::<HashSet<_>>();
+    let (mut vendors, unsupported): (Vec<_>, Vec<_>) = stored.into_iter().partition(|v| supported.contains(v));
+    if !unsupported.is_empty() {
+        warn!("skipping unsupported vendors in the database: {unsupported:?}");
+    }
+    vendors.sort();
+    vendors.dedup();
+    vendors
+}
+
+/// Orders the vendors by descending priority, configured priorities take precedence over the ones of the vendors
+fn schedule(
+    vendors: HashMap<String, Arc<dyn Vendor>>,
+    priorities: &HashMap<String, i32>,
+) -> Vec<(String, Arc<dyn Vendor>)> {
+    let priority = |name: &String, vendor: &Arc<dyn Vendor>| {
+        priorities.get(name).copied().unwrap_or_else(|| vendor.get_priority())
+    };
+    vendors
+        .into_iter()
+        .sorted_by(|(a, va), (b, vb)| priority(b, vb).cmp(&priority(a, va)).then_with(|| a.cmp(b)))
+        .collect()
+}
+
+/// Returns the configured default vendors if no vendors were given
+fn with_default_vendors(vendors: Vec<String>, default_vendors: Option<Vec<String>>) -> Vec<String> {
+    match default_vendors {
+        Some(default_vendors) if vendors.is_empty() => default_vendors,
+        _ => vendors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_redirects_of() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let location = format!("{base}/final/jdk.tar.gz");
+        let responses = [
+            format!("HTTP/1.1 302 Found\r\nlocation: {location}\r\nconnection: close\r\ncontent-length: 0\r\n\r\n"),
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 0\r\n\r\n".to_string(),
+        ];
+        let server = std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 1024]).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let item = JvmData {
+            url: format!("{base}/jdk.tar.gz"),
+            ..Default::default()
+        };
+        let http = Client::new(Duration::from_secs(5), "roast").unwrap();
+        let resolved = resolve_redirects_of(&http, "test", HashSet::from([item.clone()]));
+        server.join().unwrap();
+
+        // the URL identifies the record and is kept, the final URL is stored next to it
+        let resolved = resolved.into_iter().next().unwrap();
+        assert_eq!(resolved.url, item.url);
+        assert_eq!(resolved.resolved_url, Some(location));
+    }
+
+    #[test]
+    fn test_resume_skips_vendors() {
+        let dir = TempDir::new("resume-token");
+        let path = dir.join("resume-token.json");
+
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        assert!(!checkpoint.is_done("temurin"));
+        checkpoint.mark_done("temurin").unwrap();
+        checkpoint.mark_done("zulu").unwrap();
+
+        // an interrupted fetch resumes with the vendors finished before
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        let fetch = Fetch {
+            resume_token: Some(path.clone()),
+            ..Default::default()
+        };
+        let remaining = fetch
+            .get_vendors()
+            .into_keys()
+            .filter(|name| !checkpoint.is_done(name))
+            .collect::<HashSet<_>>();
+        assert_eq!(remaining.len(), VENDORS.len() - 2);
+        assert!(!remaining.contains("temurin"));
+        assert!(!remaining.contains("zulu"));
+        assert!(remaining.contains("corretto"));
+
+        checkpoint.finish().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let vendors = VENDORS
+            .iter()
+            .filter(|v| ["corretto", "oracle", "temurin", "zulu"].contains(&v.get_name().as_str()))
+            .map(|v| (v.get_name(), v.clone()))
+            .collect::<HashMap<_, _>>();
+
+        // the oracle scraper is fetched first, others by name
+        let names = |priorities: &HashMap<String, i32>| {
+            schedule(vendors.clone(), priorities)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&HashMap::new()), vec!["oracle", "corretto", "temurin", "zulu"]);
+
+        let priorities = HashMap::from([("zulu".to_string(), 20), ("oracle".to_string(), -1)]);
+        assert_eq!(names(&priorities), vec!["zulu", "corretto", "temurin", "oracle"]);
+    }
+
+    #[test]
+    fn test_run_vendors_serial() {
+        let vendors = schedule(
+            VENDORS.iter().map(|v| (v.get_name(), v.clone())).collect(),
+            &HashMap::new(),
+        );
+        let expected = vendors.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+
+        let pool = build_pool(true).unwrap();
+        assert_eq!(pool.current_num_threads(), 1);
+        let fetched = Mutex::new(vec![]);
+        let running = AtomicU64::new(0);
+        run_vendors(&pool, vendors.clone(), |name, vendor| {
+            assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0);
+            // parallel iterators of a vendor run on the same thread
+            let sum = (0..100u64).into_par_iter().sum::<u64>();
+            assert_eq!(sum, 4950);
+            fetched.lock().unwrap().push(vendor.get_name());
+            assert_eq!(name, vendor.get_name());
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+        assert_eq!(fetched.into_inner().unwrap(), expected);
+
+        let pool = build_pool(false).unwrap();
+        let fetched = Mutex::new(BTreeSet::new());
+        run_vendors(&pool, vendors, |name, _| {
+            fetched.lock().unwrap().insert(name);
+        });
+        assert_eq!(fetched.into_inner().unwrap().len(), VENDORS.len());
+    }
+
+    #[test]
+    fn test_with_default_vendors() {
+        let defaults = Some(vec!["temurin".to_string(), "zulu".to_string()]);
+        assert_eq!(with_default_vendors(vec![], defaults.clone()), vec!["temurin", "zulu"]);
+        assert_eq!(
+            with_default_vendors(vec!["corretto".to_string()], defaults),
+            vec!["corretto"]
+        );
+        // all vendors are fetched if no defaults are configured
+        assert!(with_default_vendors(vec![], None).is_empty());
+    }
+
+    #[test]
+    fn test_supported_vendors() {
+        let stored = ["zulu", "adoptopenjdk", "temurin", "zulu"].map(String::from).to_vec();
+        assert_eq!(supported_vendors(stored), vec!["temurin", "zulu"]);
+        assert!(supported_vendors(vec!["adoptopenjdk".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_limit_records() {
+        let jvm_data = (0..10)
+            .map(|i| JvmData {
+                url: format!("https://example.com/jdk-{i}.tar.gz"),
+                vendor: "temurin".to_string(),
+                ..Default::default()
+            })
+            .collect::<HashSet<_>>();
+
+        let limited = limit_records(jvm_data.clone(), 3, None);
+        let urls = limited.iter().map(|item| item.url.as_str()).collect::<BTreeSet<_>>();
+        assert_eq!(
+            urls,
+            BTreeSet::from([
+                "https://example.com/jdk-0.tar.gz",
+                "https://example.com/jdk-1.tar.gz",
+                "https://example.com/jdk-2.tar.gz",
+            ])
+        );
+        assert_eq!(limit_records(jvm_data.clone(), 3, None), limited);
+        assert_eq!(limit_records(jvm_data.clone(), 20, None), jvm_data);
+        assert!(limit_records(jvm_data.clone(), 0, None).is_empty());
+
+        let seeded = limit_records(jvm_data.clone(), 3, Some(42));
+        assert_eq!(seeded.len(), 3);
+        let urls = |records: &HashSet<JvmData>| records.iter().map(|item| item.url.clone()).collect::<BTreeSet<_>>();
+        assert_eq!(urls(&limit_records(jvm_data, 3, Some(42))), urls(&seeded));
+    }
+
+    #[test]
+    fn test_fetch_stats() {
+        let stats = Arc::new(FetchStats::default());
+        let threads = (0..8)
+            .map(|_| {
+                let stats = stats.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        stats.add_discovered(3);
+                        stats.add_inserted(2);
+                        stats.add_errored(1);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(stats.discovered(), 24000);
+        assert_eq!(stats.inserted(), 16000);
+        assert_eq!(stats.errored(), 8000);
+    }
+
+    #[test]
+    fn test_fetch_summary() {
+        let stats = FetchStats::default();
+        stats.add_discovered(3);
+        stats.add_inserted(2);
+        stats.add_run(VendorRun::success("temurin", Duration::from_millis(1500), 3, 2).with_pages(Some(4)));
+        let summary = stats.summary(Duration::from_secs(2));
+        assert!(summary.result().is_ok());
+
+        stats.add_errored(1);
+        stats.add_run(VendorRun::failure(
+            "zulu",
+            Duration::from_millis(200),
+            0,
+            &eyre::eyre!("failed to fetch"),
+        ));
+        let summary = stats.summary(Duration::from_secs(2));
+        assert_eq!(summary.result().unwrap_err().to_string(), "1 vendor failed to fetch");
+        // the failed vendor fails the fetch unless errors are ignored
+        let policy = |fail_fast, ignore_errors| {
+            ErrorArgs {
+                fail_fast,
+                ignore_errors,
+            }
+            .policy()
+        };
+        assert!(policy(false, false).finish(summary.result()).is_err());
+        assert!(policy(true, false).finish(summary.result()).is_err());
+        assert!(policy(false, true).finish(summary.result()).is_ok());
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            serde_json::json!({
+                "ok": false,
+                "duration_ms": 2000,
+                "discovered": 3,
+                "inserted": 2,
+                "errored": 1,
+                "vendors": {
+                    "temurin": {
+                        "duration_ms": 1500,
+                        "fetched": 3,
+                        "modified": 2,
+                        "status": "success",
+                        "error": null,
+                        "pages": 4,
+                    },
+                    "zulu": {
+                        "duration_ms": 200,
+                        "fetched": 0,
+                        "modified": 0,
+                        "status": "failure",
+                        "error": "failed to fetch",
+                    },
+                },
+            })
+        );
+
+        stats.add_errored(1);
+        let summary = stats.summary(Duration::from_secs(2));
+        assert_eq!(summary.result().unwrap_err().to_string(), "2 vendors failed to fetch");
+    }
+
+    #[test]
+    fn test_refresh_checksum() {
+        let url = "https://example.com/jdk.tar.gz";
+        let checksum_url = "https://example.com/jdk.tar.gz.sha256";
+        let old = "sha256:aaaa";
+        assert_eq!(
+            refresh_checksum(url, checksum_url, Some(old), "aaaa  jdk.tar.gz\n"),
+            None
+        );
+        assert_eq!(
+            refresh_checksum(url, checksum_url, Some(old), "BBBB  jdk.tar.gz\n"),
+            Some("sha256:bbbb".to_string())
+        );
+        assert_eq!(
+            refresh_checksum(url, checksum_url, None, "bbbb"),
+            Some("sha256:bbbb".to_string())
+        );
+        // sidecars listing several files are matched by the file name of the record
+        assert_eq!(
+            refresh_checksum(url, checksum_url, None, "ffff  jdk.zip\neeee  jdk.tar.gz\n"),
+            Some("sha256:eeee".to_string())
+        );
+        assert_eq!(
+            refresh_checksum(
+                "https://example.com/jdk.zip",
+                "https://example.com/jdk.zip.md5",
+                None,
+                "MD5 (jdk.zip) = cccc"
+            ),
+            Some("md5:cccc".to_string())
+        );
+        assert_eq!(
+            refresh_checksum(url, checksum_url, Some(old), "<html>not found</html>"),
+            None
+        );
+        assert_eq!(
+            refresh_checksum(url, "https://example.com/jdk.zip.checksum", None, "dddd"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_expand_vendor_groups() {
+        let vendor_groups = HashMap::from([
+            (
+                "lts".to_string(),
+                vec!["temurin".to_string(), "corretto".to_string(), "zulu".to_string()],
+            ),
+            ("graal".to_string(), vec!["graalvm".to_string(), "mandrel".to_string()]),
+        ]);
+
+        for (vendors, groups, expected) in [
+            (vec![], vec![], vec![]),
+            (vec!["oracle"], vec![], vec!["oracle"]),
+            (vec![], vec!["lts"], vec!["corretto", "temurin", "zulu"]),
+            (
+                vec!["zulu", "oracle"],
+                vec!["lts"],
+                vec!["corretto", "oracle", "temurin", "zulu"],
+            ),
+            (
+                vec![],
+                vec!["lts", "graal"],
+                vec!["corretto", "graalvm", "mandrel", "temurin", "zulu"],
+            ),
+        ] {
+            let vendors = vendors.into_iter().map(String::from).collect::<Vec<_>>();
+            let groups = groups.into_iter().map(String::from).collect::<Vec<_>>();
+            let actual = expand_vendor_groups(&vendors, &groups, &vendor_groups).unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        assert!(expand_vendor_groups(&[], &["unknown".to_string()], &vendor_groups).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+use log::warn;
+
+/// Flags filtering records by their properties
+#[derive(Debug, clap::Args)]
+pub struct FilterArgs {
+    /// Filters to apply to the data e.g.: os=linux,macosx&architecture=x86_64,aarch64&features=musl,javafx,!lite
+    ///
+    /// Filters are separated with '&' and all of them must match. Values are separated with ',' and a filter matches
+    /// if any of its values match and none of its values negated with '!' do. For example features=musl,javafx,!lite
+    /// matches entries where the array `features` includes musl or javafx but not lite. Filters of the same property
+    /// are merged.
+    #[clap(short = 'f', long, num_args = 0.., value_delimiter = '&', value_name = "FILTER")]
+    pub filters: Option<Vec<String>>,
+    /// File with one filter per line which are merged with --filters, blank lines and lines starting with # are skipped
+    #[clap(long, value_name = "FILE")]
+    pub filter_file: Option<PathBuf>,
+}
+
+impl FilterArgs {
+    /// Returns the values of each filtered property of the filters and the filter file
+    pub fn filter_map(&self) -> Result<HashMap<String, Vec<String>>> {
+        Ok(get_filter_map(get_filters(
+            self.filters.clone(),
+            self.filter_file.as_deref(),
+        )?))
+    }
+}
+
+/// Reads the lines of a list file, blank lines and lines starting with `#` are skipped
+pub fn read_list_file(path: &Path, kind: &str) -> Result<Vec<String>> {
+    let content =
+        fs::read_to_string(path).wrap_err_with(|| format!("failed to read {kind} file {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Returns the given filters extended by the filters of the file
+///
+/// The file contains one filter expression per line with the grammar of `--filters`, blank lines and lines starting
+/// with `#` are skipped.
+fn get_filters(filters: Option<Vec<String>>, filter_file: Option<&Path>) -> Result<Vec<String>> {
+    let mut result = filters.unwrap_or_default();
+    if let Some(path) = filter_file {
+        let lines = read_list_file(path, "filter")?;
+        result.extend(lines.iter().flat_map(|line| line.split('&')).map(String::from));
+    }
+    Ok(result)
+}
+
+/// Returns the values of each filtered property, filters of the same property are merged
+///
+/// Each filter has the form `property=value,value,!value`, blanks around properties and values are trimmed and empty
+/// values are skipped. Filters without a single `=` are skipped with a warning.
+fn get_filter_map(filters: Vec<String>) -> HashMap<String, Vec<String>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for filter in filters {
+        let parts: Vec<&str> = filter.split('=').collect();
+        if parts.len() != 2 || parts[0].trim().is_empty() {
+            if !filter.trim().is_empty() {
+                warn!("skipping invalid filter {filter}, expected property=value");
+            }
+            continue;
+        }
+        let key = parts[0].trim().to_string();
+        let value = parts[1]
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        map.entry(key).or_default().extend(value);
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{jvm::JvmData, test_util::TempDir};
+
+    use super::*;
+
+    #[test]
+    fn test_get_filter_map() {
+        let filters = [
+            "os=linux,macosx",
+            "architecture = x86_64, aarch64,",
+            "os=!windows",
+            "invalid",
+            "",
+        ];
+        let filter_map = get_filter_map(filters.map(String::from).to_vec());
+        assert_eq!(filter_map["os"], vec!["linux", "macosx", "!windows"]);
+        assert_eq!(filter_map["architecture"], vec!["x86_64", "aarch64"]);
+        assert_eq!(filter_map.len(), 2);
+
+        // every filter must match with any of its values
+        for (os, architecture, expected) in [
+            ("linux", "x86_64", true),
+            ("linux", "aarch64", true),
+            ("macosx", "aarch64", true),
+            ("macosx", "arm32", false),
+            ("windows", "x86_64", false),
+            ("solaris", "sparcv9", false),
+        ] {
+            let item = JvmData {
+                os: os.to_string(),
+                architecture: architecture.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(JvmData::filter(&item, &filter_map), expected, "{os} {architecture}");
+        }
+    }
+
+    #[test]
+    fn test_get_filters() {
+        let dir = TempDir::new("filters");
+        let path = dir.join("filters.txt");
+        fs::write(
+            &path,
+            "# archives only\nfile_type=tar.gz,zip\n\n  features=musl,javafx,!lite  \n# jdk\nimage_type=jdk&os=linux\n",
+        )
+        .unwrap();
+
+        let inline = [
+            "file_type=tar.gz,zip",
+            "features=musl,javafx,!lite",
+            "image_type=jdk",
+            "os=linux",
+        ]
+        .map(String::from)
+        .to_vec();
+        let from_file = get_filters(None, Some(&path)).unwrap();
+        assert_eq!(from_file, inline);
+        assert_eq!(get_filter_map(from_file), get_filter_map(inline));
+
+        // filters of the file are merged with the given ones
+        let merged = get_filters(Some(vec!["features=crac".to_string()]), Some(&path)).unwrap();
+        let filter_map = get_filter_map(merged);
+        assert_eq!(filter_map["features"], vec!["crac", "musl", "javafx", "!lite"]);
+        assert_eq!(filter_map.len(), 4);
+
+        fs::remove_file(&path).unwrap();
+        assert!(get_filters(None, Some(&path)).is_err());
+    }
+}
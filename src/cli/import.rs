@@ -0,0 +1,135 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use eyre::{Result, WrapErr};
+use log::info;
+
+use crate::{
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+    jvm::JvmData,
+};
+
+/// Import JVM data from exported JSON files
+///
+/// Files must contain an array of complete records as written by `roast export` without
+/// --include, --exclude or --fields. Records which already exist are skipped unless --merge is set e.g.:
+///   roast import public/api/jvm/ga/linux/x86_64.json
+///   roast import --merge curated.json
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Import {
+    /// JSON files to import
+    #[clap(required = true, value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+    /// Update the changed fields of existing records instead of skipping them
+    #[clap(long, default_value = "false")]
+    pub merge: bool,
+}
+
+impl Import {
+    pub fn run(self) -> Result<()> {
+        let mut jvm_data = HashSet::new();
+        for file in &self.files {
+            let json = fs::read_to_string(file).wrap_err_with(|| format!("failed to read {}", file.display()))?;
+            let records = parse_records(&json).wrap_err_with(|| format!("failed to parse {}", file.display()))?;
+            info!("read {} records from {}", records.len(), file.display());
+            jvm_data.extend(records);
+        }
+
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        let result = match self.merge {
+            true => db.insert(&jvm_data)?,
+            false => db.insert_new(&jvm_data)?,
+        };
+        info!("imported {} records, inserted/modified {result}", jvm_data.len());
+        Ok(())
+    }
+}
+
+/// Parses the records of an exported JSON file
+fn parse_records(json: &str) -> Result<Vec<JvmData>> {
+    let records = serde_json::from_str::<Vec<JvmData>>(json)?
+        .into_iter()
+        .map(|mut item| {
+            // exports write records without features as an empty array
+            if item.features.as_ref().is_some_and(|f| f.is_empty()) {
+                item.features = None;
+            }
+            // files written before the build was recorded or curated by hand may lack it
+            item.derive_build();
+            item.prefer_strongest_checksum();
+            item
+        })
+        .collect();
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_records() {
+        let item = JvmData {
+            architecture: "x86_64".to_string(),
+            build: Some("12".to_string()),
+            checksum: Some("sha256:abcd".to_string()),
+            checksum_url: Some("https://example.com/jdk.tar.gz.sha256".to_string()),
+            checksums: vec!["md5:0123".to_string(), "sha256:abcd".to_string()],
+            file_type: "tar.gz".to_string(),
+            filename: "jdk.tar.gz".to_string(),
+            image_type: "jdk".to_string(),
+            java_version: "21.0.1".to_string(),
+            os: "linux".to_string(),
+            release_type: "ga".to_string(),
+            resolved_url: Some("https://cdn.example.com/jdk.tar.gz".to_string()),
+            size: Some(12345678),
+            url: "https://example.com/jdk.tar.gz".to_string(),
+            vendor: "temurin".to_string(),
+            version: "21.0.1+12".to_string(),
+            ..Default::default()
+        };
+        let musl = JvmData {
+            build: None,
+            checksum: None,
+            checksums: vec![],
+            features: Some(vec!["musl".to_string()]),
+            url: "https://example.com/jdk-musl.tar.gz".to_string(),
+            version: "21.0.1".to_string(),
+            ..item.clone()
+        };
+        let export = |items: &[&JvmData]| {
+            let maps = items
+                .iter()
+                .map(|item| JvmData::map(item, &[], &[]))
+                .collect::<Vec<_>>();
+            serde_json::to_string(&maps).unwrap()
+        };
+
+        // exported records are imported unchanged
+        let records = parse_records(&export(&[&item, &musl])).unwrap();
+        assert_eq!(
+            serde_json::to_value(&records).unwrap(),
+            serde_json::to_value([&item, &musl]).unwrap()
+        );
+
+        // a weaker primary checksum is kept along with the strongest one
+        let weaker = JvmData {
+            checksum: Some("md5:0123".to_string()),
+            checksums: vec!["sha256:abcd".to_string()],
+            ..item.clone()
+        };
+        let records = parse_records(&export(&[&weaker])).unwrap();
+        assert_eq!(records[0].checksum, item.checksum);
+        assert_eq!(records[0].checksums, ["sha256:abcd", "md5:0123"]);
+
+        // the build of files written before it was recorded is derived from the version
+        let mut map = JvmData::map(&item, &[], &[]);
+        map.remove("build");
+        let records = parse_records(&serde_json::to_string(&[map]).unwrap()).unwrap();
+        assert_eq!(records[0].build, item.build);
+
+        // exports with --include, --exclude or --fields lack required properties
+        assert!(parse_records(r#"[{"url": "https://example.com/jdk.tar.gz"}]"#).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use eyre::Result;
+use log::warn;
 
 use crate::{
     config::Conf,
@@ -17,6 +18,10 @@ impl Arch {
         }
         let conn_pool = ConnectionPool::get_pool()?;
         let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
 
         let archs = db.get_distinct("architecture")?;
         for arch in &archs {
@@ -0,0 +1,68 @@
+use eyre::Result;
+use log::warn;
+
+use crate::{
+    config::Conf,
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+    jvm::compare_versions,
+};
+
+/// List the distinct Java versions
+///
+/// Unlike the product version of a release, the Java version is the version of the bundled JDK
+/// which differs for GraalVM based vendors e.g. liberica-nik, oracle-graalvm
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct JavaVersions {
+    /// Only list the Java versions of the given vendor e.g.: liberica-nik
+    #[clap(long, value_name = "VENDOR")]
+    pub vendor: Option<String>,
+}
+
+impl JavaVersions {
+    pub fn run(self) -> Result<()> {
+        let conf = Conf::try_get()?;
+        if conf.export.path.is_none() {
+            return Err(eyre::eyre!("export.path is not configured"));
+        }
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
+
+        let versions = match &self.vendor {
+            Some(vendor) => db.get_distinct_by_vendor("java_version", vendor)?,
+            None => db.get_distinct("java_version")?,
+        };
+        for version in sort_versions(versions) {
+            println!("{version}");
+        }
+        Ok(())
+    }
+}
+
+/// Sorts the versions semantically and removes duplicates and empty versions
+fn sort_versions(mut versions: Vec<String>) -> Vec<String> {
+    versions.retain(|version| !version.is_empty());
+    versions.sort_by(|a, b| compare_versions(a, b));
+    versions.dedup();
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_versions() {
+        let versions = ["21.0.2", "17.0.10+7", "8", "", "21.0.10", "17.0.10+7", "11.0.22", "21"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            sort_versions(versions),
+            vec!["8", "11.0.22", "17.0.10+7", "21", "21.0.2", "21.0.10"]
+        );
+    }
+}
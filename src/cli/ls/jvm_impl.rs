@@ -0,0 +1,32 @@
+use eyre::Result;
+use log::warn;
+
+use crate::{
+    config::Conf,
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+};
+
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct JvmImpl {}
+
+impl JvmImpl {
+    pub fn run(self) -> Result<()> {
+        let conf = Conf::try_get()?;
+        if conf.export.path.is_none() {
+            return Err(eyre::eyre!("export.path is not configured"));
+        }
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
+
+        let jvm_impls = db.get_distinct("jvm_impl")?;
+        for jvm_impl in &jvm_impls {
+            println!("{}", jvm_impl);
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,21 @@
 use clap::Subcommand;
 
 mod arch;
+mod java_versions;
+mod jvm_impl;
 mod os;
+mod releases;
+mod runs;
 mod vendors;
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     Arch(arch::Arch),
+    JavaVersions(java_versions::JavaVersions),
+    JvmImpl(jvm_impl::JvmImpl),
     Os(os::Os),
+    Releases(releases::Releases),
+    Runs(runs::Runs),
     Vendor(vendors::Vendor),
 }
 
@@ -15,7 +23,11 @@ impl Commands {
     pub fn run(self) -> eyre::Result<()> {
         match self {
             Self::Arch(cmd) => cmd.run(),
+            Self::JavaVersions(cmd) => cmd.run(),
+            Self::JvmImpl(cmd) => cmd.run(),
             Self::Os(cmd) => cmd.run(),
+            Self::Releases(cmd) => cmd.run(),
+            Self::Runs(cmd) => cmd.run(),
             Self::Vendor(cmd) => cmd.run(),
         }
     }
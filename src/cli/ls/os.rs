@@ -1,6 +1,8 @@
 use eyre::Result;
+use log::warn;
 
 use crate::{
+    cli::export::split_musl_os,
     config::Conf,
     db::{jvm_repository::JvmRepository, pool::ConnectionPool},
 };
@@ -17,8 +19,12 @@ impl Os {
         }
         let conn_pool = ConnectionPool::get_pool()?;
         let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
 
-        let oses = db.get_distinct("os")?;
+        let oses = split_musl_os(db.get_distinct("os")?, conf.export.musl_os.as_deref());
         for os in &oses {
             println!("{}", os);
         }
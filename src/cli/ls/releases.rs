@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+use log::warn;
+
+use crate::{
+    cli::filters::FilterArgs,
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+    jvm::{JvmData, compare_versions},
+};
+
+/// List the version and URL of the matching records
+///
+/// Unlike export no files are written and at least one of --vendor, --os or --arch is required, e.g.:
+///   roast ls releases --vendor temurin --os linux --arch x86_64
+///   roast ls releases --vendor zulu --filters "features=crac&image_type=jdk"
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+#[clap(group(clap::ArgGroup::new("scope").args(["vendor", "os", "arch"]).multiple(true).required(true)))]
+pub struct Releases {
+    /// Vendor e.g.: temurin
+    #[clap(long, value_name = "VENDOR")]
+    pub vendor: Option<String>,
+    /// Operating system e.g.: linux
+    #[clap(long, value_name = "OS")]
+    pub os: Option<String>,
+    /// Architecture e.g.: x86_64
+    #[clap(long, value_name = "ARCH")]
+    pub arch: Option<String>,
+    #[clap(flatten)]
+    pub filters: FilterArgs,
+}
+
+impl Releases {
+    pub fn run(self) -> Result<()> {
+        let filters = self.filters.filter_map()?;
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
+
+        let (columns, values): (Vec<&str>, Vec<String>) =
+            [("vendor", self.vendor), ("os", self.os), ("architecture", self.arch)]
+                .into_iter()
+                .filter_map(|(column, value)| Some((column, value?)))
+                .unzip();
+        let records = db.export_by(&columns, &values)?;
+        let releases = list_releases(records, &filters);
+        if releases.is_empty() {
+            println!("no matching releases");
+        }
+        for release in releases {
+            println!("{release}");
+        }
+        Ok(())
+    }
+}
+
+/// Returns the version and URL of the records matching the filters, sorted by version
+fn list_releases(mut records: Vec<JvmData>, filters: &HashMap<String, Vec<String>>) -> Vec<String> {
+    records.retain(|item| JvmData::filter(item, filters));
+    records.sort_by(|a, b| compare_versions(&a.version, &b.version).then_with(|| a.url.cmp(&b.url)));
+    records
+        .iter()
+        .map(|item| format!("{} {}", item.version, item.url))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_releases() {
+        let record = |version: &str, url: &str, image_type: &str| JvmData {
+            image_type: image_type.to_string(),
+            url: url.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        };
+        let records = vec![
+            record("21.0.2+13", "https://example.com/jdk-21.0.2.tar.gz", "jdk"),
+            record("17.0.10+7", "https://example.com/jdk-17.0.10.tar.gz", "jdk"),
+            record("17.0.10+7", "https://example.com/jre-17.0.10.tar.gz", "jre"),
+            record("21.0.2+9", "https://example.com/jdk-21.0.2-early.tar.gz", "jdk"),
+        ];
+
+        assert_eq!(
+            list_releases(records.clone(), &HashMap::new()),
+            vec![
+                "17.0.10+7 https://example.com/jdk-17.0.10.tar.gz",
+                "17.0.10+7 https://example.com/jre-17.0.10.tar.gz",
+                "21.0.2+9 https://example.com/jdk-21.0.2-early.tar.gz",
+                "21.0.2+13 https://example.com/jdk-21.0.2.tar.gz",
+            ]
+        );
+
+        let filters = FilterArgs {
+            filters: Some(vec!["image_type=jre".to_string()]),
+            filter_file: None,
+        };
+        let filters = filters.filter_map().unwrap();
+        assert_eq!(
+            list_releases(records, &filters),
+            vec!["17.0.10+7 https://example.com/jre-17.0.10.tar.gz"]
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use eyre::Result;
+
+use crate::db::{pool::ConnectionPool, vendor_run_repository::VendorRunRepository};
+
+/// List the last fetch of each vendor
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Runs {}
+
+impl Runs {
+    pub fn run(self) -> Result<()> {
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = VendorRunRepository::new(conn_pool)?;
+
+        for run in db.find_all()? {
+            println!(
+                "{vendor} {fetched_at} {status} fetched={fetched} modified={modified} duration={duration:.2}s{error}",
+                vendor = run.vendor,
+                fetched_at = run.fetched_at.unwrap_or_default(),
+                status = run.status,
+                fetched = run.fetched,
+                modified = run.modified,
+                duration = run.duration_ms as f64 / 1000.0,
+                error = run.error.map(|e| format!(" error={e}")).unwrap_or_default(),
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,27 +1,55 @@
 use std::{collections::HashMap, sync::Arc};
 
 use eyre::Result;
+use itertools::Itertools;
+use log::warn;
 
 use crate::{
     config::Conf,
     db::{jvm_repository::JvmRepository, pool::ConnectionPool},
-    jvm::vendor::VENDORS,
+    jvm::vendor::{VENDORS, VendorMeta},
 };
 
 #[derive(Debug, clap::Args)]
 #[clap(verbatim_doc_comment)]
-pub struct Vendor {}
+pub struct Vendor {
+    /// Print a table with the meta data of all supported vendors, see vendor-info for the details of one
+    #[clap(long, default_value = "false")]
+    pub detailed: bool,
+    /// List all supported vendors including those without any data yet
+    #[clap(long, default_value = "false")]
+    pub all: bool,
+}
 
 impl Vendor {
     pub fn run(self) -> Result<()> {
+        if self.detailed {
+            let mut metas = VENDORS.iter().map(|v| v.get_meta()).collect::<Vec<_>>();
+            metas.sort_by(|a, b| a.name.cmp(&b.name));
+            print!("{}", format_table(&metas));
+            return Ok(());
+        }
+
         let conf = Conf::try_get()?;
         if conf.export.path.is_none() {
             return Err(eyre::eyre!("export.path is not configured"));
         }
         let conn_pool = ConnectionPool::get_pool()?;
         let db = JvmRepository::new(conn_pool)?;
+        if let Some(message) = db.get_data_state()?.no_data_message() {
+            warn!("{message}");
+            return Ok(());
+        }
 
         let vendors = db.get_distinct("vendor")?;
+        if self.all {
+            let registered = VENDORS.iter().map(|v| v.get_name()).collect::<Vec<_>>();
+            for line in format_all(&registered, &vendors) {
+                println!("{line}");
+            }
+            return Ok(());
+        }
+
         let vendors_map = self.get_vendors();
         for vendor in &vendors {
             // skip vendors that are not supported
@@ -37,3 +65,112 @@ impl Vendor {
         VENDORS.iter().map(|v| (v.get_name(), v.to_owned())).collect()
     }
 }
+
+/// Formats the registered vendors sorted by name and whether the database contains data for them
+fn format_all(registered: &[String], with_data: &[String]) -> Vec<String> {
+    let width = registered.iter().map(|name| name.len()).max().unwrap_or_default();
+    registered
+        .iter()
+        .sorted()
+        .map(|name| match with_data.contains(name) {
+            true => format!("{name:width$}  has data"),
+            false => format!("{name:width$}  no data yet"),
+        })
+        .collect()
+}
+
+/// Formats the meta data of the vendors as a table with aligned columns
+fn format_table(metas: &[VendorMeta]) -> String {
+    let header = ["NAME", "KIND", "IMPL", "AUTH", "HOMEPAGE", "LICENSE"].map(String::from);
+    let rows = metas.iter().map(|meta| {
+        [
+            meta.name.clone(),
+            meta.kind.to_string(),
+            meta.impl_kind.to_string(),
+            if meta.auth_required { "yes" } else { "no" }.to_string(),
+            meta.homepage.clone(),
+            meta.license_note.clone(),
+        ]
+    });
+    let rows = std::iter::once(header).chain(rows).collect::<Vec<_>>();
+
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    for row in &rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jvm::{JvmImpl, vendor::VendorKind};
+
+    use super::*;
+
+    #[test]
+    fn test_format_all() {
+        let registered = VENDORS.iter().map(|v| v.get_name()).collect::<Vec<_>>();
+        let with_data = vec!["temurin".to_string(), "zulu".to_string(), "unsupported".to_string()];
+        let lines = format_all(&registered, &with_data);
+
+        assert_eq!(lines.len(), VENDORS.len());
+        let names = lines
+            .iter()
+            .map(|line| line.split_whitespace().next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, registered.iter().cloned().sorted().collect::<Vec<_>>());
+        for line in &lines {
+            let name = line.split_whitespace().next().unwrap();
+            match name {
+                "temurin" | "zulu" => assert!(line.ends_with("has data"), "{line}"),
+                _ => assert!(line.ends_with("no data yet"), "{line}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_table() {
+        let metas = [
+            VendorMeta {
+                name: "oracle".to_string(),
+                kind: VendorKind::Scraper,
+                homepage: "https://www.oracle.com/java/".to_string(),
+                impl_kind: JvmImpl::Hotspot,
+                license_note: "NFTC".to_string(),
+                auth_required: false,
+                urls: vec!["https://www.oracle.com/java/technologies/downloads/".to_string()],
+                notes: vec![],
+            },
+            VendorMeta {
+                name: "semeru".to_string(),
+                kind: VendorKind::Api,
+                homepage: "https://example.com/".to_string(),
+                impl_kind: JvmImpl::Openj9,
+                license_note: "GPLv2".to_string(),
+                auth_required: true,
+                urls: vec![],
+                notes: vec![],
+            },
+        ];
+        let expected = [
+            "NAME    KIND     IMPL     AUTH  HOMEPAGE                      LICENSE",
+            "oracle  scraper  hotspot  no    https://www.oracle.com/java/  NFTC",
+            "semeru  api      openj9   yes   https://example.com/          GPLv2",
+        ];
+        assert_eq!(format_table(&metas), format!("{}\n", expected.join("\n")));
+    }
+}
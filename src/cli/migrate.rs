@@ -0,0 +1,123 @@
+use eyre::Result;
+use log::info;
+
+use crate::db::{
+    migration_repository::{MIGRATIONS, Migration, MigrationRepository},
+    pool::ConnectionPool,
+};
+
+/// Apply pending schema migrations
+///
+/// Applies the migrations of ./sql/migrations which were not applied yet and prints the resulting schema version.
+/// Has to connect as the owner of the tables, the roast user may only read the schema version e.g.:
+///   roast --database-url postgres://postgres@localhost:5432/roast migrate
+///   roast migrate --status
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Migrate {
+    /// Print the current and the latest schema version without applying migrations
+    #[clap(long, default_value = "false")]
+    pub status: bool,
+}
+
+impl Migrate {
+    pub fn run(self) -> Result<()> {
+        let conn_pool = ConnectionPool::get_pool()?;
+        let db = MigrationRepository::new(conn_pool)?;
+        let current = db.get_version()?;
+        if self.status {
+            println!("{}", status(current, MIGRATIONS));
+            return Ok(());
+        }
+        let version = apply_pending(current, MIGRATIONS, |migration| {
+            info!("applying migration {:04}_{}", migration.version, migration.name);
+            db.apply(migration)
+        })?;
+        println!("schema version {version}");
+        Ok(())
+    }
+}
+
+fn pending(current: u32, migrations: &[Migration]) -> impl Iterator<Item = &Migration> {
+    migrations.iter().filter(move |migration| migration.version > current)
+}
+
+/// Applies the migrations newer than the current version in order and returns the resulting version
+///
+/// Stops at the first failing migration, the ones applied before are kept.
+fn apply_pending(
+    current: u32,
+    migrations: &[Migration],
+    mut apply: impl FnMut(&Migration) -> Result<()>,
+) -> Result<u32> {
+    let mut version = current;
+    for migration in pending(current, migrations) {
+        apply(migration)?;
+        version = migration.version;
+    }
+    Ok(version)
+}
+
+fn status(current: u32, migrations: &[Migration]) -> String {
+    let latest = migrations
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default();
+    match pending(current, migrations).count() {
+        0 => format!("schema version {current} is up to date"),
+        count => format!("schema version {current}, latest {latest}, {count} pending"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations() {
+        let versions = MIGRATIONS.iter().map(|migration| migration.version).collect::<Vec<_>>();
+        assert_eq!(versions, (1..=MIGRATIONS.len() as u32).collect::<Vec<_>>());
+        let schema = include_str!("../../sql/schema.sql");
+        let recorded = versions.iter().map(|v| format!("({v})")).collect::<Vec<_>>().join(", ");
+        assert!(schema.contains(&format!("INSERT INTO SCHEMA_MIGRATIONS (version) VALUES {recorded};")));
+    }
+
+    #[test]
+    fn test_apply_pending_in_order_until_failure() {
+        let latest = MIGRATIONS.len() as u32;
+        assert_eq!(
+            status(2, MIGRATIONS),
+            format!("schema version 2, latest {latest}, {} pending", latest - 2)
+        );
+
+        let mut applied = vec![];
+        let version = apply_pending(2, MIGRATIONS, |migration| {
+            applied.push(migration.version);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(version, latest);
+        assert_eq!(applied, (3..=latest).collect::<Vec<_>>());
+        assert_eq!(
+            status(version, MIGRATIONS),
+            format!("schema version {latest} is up to date")
+        );
+
+        // nothing is applied to an up to date schema
+        let version = apply_pending(latest, MIGRATIONS, |_| panic!("unexpected migration")).unwrap();
+        assert_eq!(version, latest);
+
+        // a failing migration stops before later ones
+        let mut applied = vec![];
+        let result = apply_pending(0, MIGRATIONS, |migration| {
+            if migration.version == 3 {
+                return Err(eyre::eyre!("failed"));
+            }
+            applied.push(migration.version);
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(applied, vec![1, 2]);
+    }
+}
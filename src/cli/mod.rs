@@ -2,27 +2,56 @@ use clap::{FromArgMatches, Subcommand};
 use color_eyre::Result;
 use indoc::indoc;
 
+mod audit;
+mod completions;
+mod config;
+mod db;
+mod diff;
+mod errors;
 mod export;
 mod fetch;
+mod filters;
+mod import;
 mod ls;
+mod migrate;
+mod normalize;
+mod vendor_info;
 pub mod version;
 
 pub struct Cli {}
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
+    Audit(audit::Audit),
+    Completions(completions::Completions),
+    Config(config::Config),
+    Db(db::Db),
+    Diff(diff::Diff),
     Export(export::Export),
     Fetch(fetch::Fetch),
+    Import(import::Import),
     Ls(ls::Ls),
+    Migrate(migrate::Migrate),
+    Normalize(normalize::Normalize),
+    VendorInfo(vendor_info::VendorInfo),
     Version(version::Version),
 }
 
 impl Commands {
     pub fn run(self) -> Result<()> {
         match self {
+            Self::Audit(cmd) => cmd.run(),
+            Self::Completions(cmd) => cmd.run(),
+            Self::Config(cmd) => cmd.run(),
+            Self::Db(cmd) => cmd.run(),
+            Self::Diff(cmd) => cmd.run(),
             Self::Export(cmd) => cmd.run(),
             Self::Fetch(cmd) => cmd.run(),
+            Self::Import(cmd) => cmd.run(),
             Self::Ls(cmd) => cmd.run(),
+            Self::Migrate(cmd) => cmd.run(),
+            Self::Normalize(cmd) => cmd.run(),
+            Self::VendorInfo(cmd) => cmd.run(),
             Self::Version(cmd) => cmd.run(),
         }
     }
@@ -37,7 +66,22 @@ impl Cli {
                 .author("Roland Schär <@roele>")
                 .long_about(LONG_ABOUT)
                 .arg_required_else_help(true)
-                .subcommand_required(true),
+                .subcommand_required(true)
+                .arg(
+                    clap::Arg::new("config_file")
+                        .long("config")
+                        .global(true)
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(std::path::PathBuf))
+                        .help("Configuration file loaded instead of ROAST_CONFIG, ./roast.toml, ./config.toml and the user one"),
+                )
+                .arg(
+                    clap::Arg::new("database_url")
+                        .long("database-url")
+                        .global(true)
+                        .value_name("URL")
+                        .help("Database connection URL, overrides database.url of the configuration"),
+                ),
         )
     }
 
@@ -51,6 +95,13 @@ impl Cli {
 
         // debug!("ARGS: {}", &args.join(" "));
 
+        if let Some(path) = matches.get_one::<std::path::PathBuf>("config_file") {
+            crate::env::CONFIG.write().unwrap().replace(path.clone());
+        }
+        if let Some(url) = matches.get_one::<String>("database_url") {
+            crate::env::DATABASE_URL.write().unwrap().replace(url.clone());
+        }
+
         match Commands::from_arg_matches(&matches) {
             Ok(cmd) => cmd.run(),
             Err(err) => matches.subcommand().ok_or(err).map(|_| {
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use log::{debug, info};
+
+use crate::{
+    config::Conf,
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+    jvm::{
+        JvmData,
+        vendor::{Overrides, is_valid_version, normalize_architecture, normalize_os, normalize_version, parse_build},
+    },
+};
+
+/// Re-normalize stored records
+///
+/// Re-runs the normalization of the OS, architecture, version and build of the stored records including the overrides
+/// of normalize.os and normalize.architecture and updates the changed ones in place, e.g. after a normalization fix
+/// without crawling the vendors again:
+///   roast normalize
+///   roast normalize --dry-run temurin zulu
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Normalize {
+    /// Vendors to normalize e.g.: openjdk, zulu
+    #[clap(value_name = "VENDOR")]
+    pub vendors: Vec<String>,
+    /// Print the number of records which would change without updating them
+    #[clap(long, default_value = "false")]
+    pub dry_run: bool,
+}
+
+impl Normalize {
+    pub fn run(self) -> Result<()> {
+        let conf = Conf::try_get()?;
+        let overrides = Overrides::new(
+            conf.normalize.os.unwrap_or_default(),
+            conf.normalize.architecture.unwrap_or_default(),
+        );
+
+        let db = JvmRepository::new(ConnectionPool::get_pool()?)?;
+        let vendors = match self.vendors.is_empty() {
+            true => db.get_distinct("vendor")?,
+            false => self.vendors,
+        };
+        let mut total = 0;
+        let mut changed = HashSet::new();
+        for vendor in vendors {
+            let records = db.export_by(&["vendor"], std::slice::from_ref(&vendor))?;
+            total += records.len();
+            let renormalized = records
+                .iter()
+                .filter_map(|item| renormalize(item, &overrides))
+                .collect::<Vec<_>>();
+            info!("[{vendor}] {} of {} records changed", renormalized.len(), records.len());
+            changed.extend(renormalized);
+        }
+
+        if self.dry_run {
+            println!("{} of {total} records would change", changed.len());
+            return Ok(());
+        }
+        let result = match changed.is_empty() {
+            true => 0,
+            false => db.insert(&changed)?,
+        };
+        println!("{} of {total} records changed, updated {result}", changed.len());
+        Ok(())
+    }
+}
+
+/// Returns the record with its OS, architecture and version normalized again, none if nothing changed
+///
+/// The stored values are normalized unless they are already canonical. Values which are still unknown or invalid
+/// after normalizing are kept.
+fn renormalize(item: &JvmData, overrides: &Overrides) -> Option<JvmData> {
+    let os = renormalize_token(&item.os, "unknown-os-", |os| normalize_os(os, overrides));
+    let architecture = renormalize_token(&item.architecture, "unknown-arch-", |architecture| {
+        normalize_architecture(architecture, overrides)
+    });
+    let version = Some(normalize_version(&item.version))
+        .filter(|version| is_valid_version(version))
+        .unwrap_or_else(|| item.version.clone());
+    let build = parse_build(&version);
+    if os == item.os && architecture == item.architecture && version == item.version && build == item.build {
+        return None;
+    }
+    debug!(
+        "normalized {}: {} -> {os}, {} -> {architecture}, {} -> {version}, {:?} -> {build:?}",
+        item.url, item.os, item.architecture, item.version, item.build
+    );
+    Some(JvmData {
+        os,
+        architecture,
+        version,
+        build,
+        ..item.clone()
+    })
+}
+
+/// Normalizes the raw token of a stored value, canonical values are normalized as unknown and therefore kept
+fn renormalize_token(value: &str, unknown_prefix: &str, normalize: impl Fn(&str) -> String) -> String {
+    let token = value.strip_prefix(unknown_prefix).unwrap_or(value);
+    let normalized = normalize(token);
+    match normalized.starts_with(unknown_prefix) {
+        true => value.to_string(),
+        false => normalized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renormalize() {
+        let item = JvmData {
+            architecture: "x86_64".to_string(),
+            os: "linux".to_string(),
+            url: "https://example.com/jdk-21.0.1-linux-x64.tar.gz".to_string(),
+            version: "21.0.1".to_string(),
+            ..Default::default()
+        };
+        assert!(renormalize(&item, &Overrides::default()).is_none());
+
+        // records stored before the normalization knew the tokens are corrected
+        let stored = JvmData {
+            architecture: "unknown-arch-amd64".to_string(),
+            os: "darwin".to_string(),
+            version: "21".to_string(),
+            ..item.clone()
+        };
+        let actual = renormalize(&stored, &Overrides::default()).unwrap();
+        assert_eq!(actual.architecture, "x86_64");
+        assert_eq!(actual.os, "macosx");
+        assert_eq!(actual.version, "21.0.0");
+        assert_eq!(actual.url, item.url);
+
+        // canonical values which are not tokens of the normalization and unknown ones are kept
+        let stored = JvmData {
+            architecture: "ppc32".to_string(),
+            os: "unknown-os-plan9".to_string(),
+            ..item.clone()
+        };
+        assert!(renormalize(&stored, &Overrides::default()).is_none());
+
+        // records stored before the build was recorded get it derived from the version
+        let stored = JvmData {
+            version: "21.0.1+12".to_string(),
+            ..item.clone()
+        };
+        assert_eq!(
+            renormalize(&stored, &Overrides::default()).unwrap().build.as_deref(),
+            Some("12")
+        );
+    }
+}
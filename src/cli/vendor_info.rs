@@ -0,0 +1,96 @@
+use eyre::Result;
+use log::warn;
+
+use crate::{
+    db::{jvm_repository::JvmRepository, pool::ConnectionPool},
+    jvm::vendor::{VENDORS, Vendor},
+};
+
+/// Show information about a vendor
+///
+/// Prints the meta data of a vendor e.g. the kind of crawler, the crawled URLs and notes as well
+/// as the operating systems and architectures found in the database
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct VendorInfo {
+    /// Vendor e.g.: openjdk, zulu
+    #[clap(value_name = "VENDOR")]
+    pub vendor: String,
+}
+
+impl VendorInfo {
+    pub fn run(self) -> Result<()> {
+        let vendor = VENDORS
+            .iter()
+            .find(|v| v.get_name() == self.vendor)
+            .ok_or_else(|| eyre::eyre!("unknown vendor {}", self.vendor))?;
+
+        for line in format_info(vendor.as_ref()) {
+            println!("{line}");
+        }
+
+        // the database is optional, e.g. before the first fetch
+        match get_coverage(&self.vendor) {
+            Ok((oses, archs)) => {
+                println!("os:    {}", oses.join(", "));
+                println!("arch:  {}", archs.join(", "));
+            }
+            Err(err) => warn!("unable to read coverage from database: {err}"),
+        }
+        Ok(())
+    }
+}
+
+fn format_info(vendor: &dyn Vendor) -> Vec<String> {
+    let meta = vendor.get_meta();
+    let mut lines = vec![
+        format!("name:  {}", meta.name),
+        format!("kind:  {}", meta.kind),
+        format!("impl:  {}", meta.impl_kind),
+        format!("home:  {}", meta.homepage),
+        format!("license: {}", meta.license_note),
+        format!(
+            "auth:  {}",
+            if meta.auth_required { "required" } else { "not required" }
+        ),
+    ];
+    lines.extend(meta.urls.iter().map(|url| format!("url:   {url}")));
+    lines.extend(
+        vendor
+            .get_headers()
+            .iter()
+            .map(|(name, value)| format!("header: {name}: {value}")),
+    );
+    lines.extend(meta.notes.iter().map(|note| format!("note:  {note}")));
+    lines
+}
+
+fn get_coverage(vendor: &str) -> Result<(Vec<String>, Vec<String>)> {
+    let conn_pool = ConnectionPool::get_pool()?;
+    let db = JvmRepository::new(conn_pool)?;
+    let oses = db.get_distinct_by_vendor("os", vendor)?;
+    let archs = db.get_distinct_by_vendor("architecture", vendor)?;
+    Ok((oses, archs))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jvm::vendor::oracle::Oracle;
+
+    use super::*;
+
+    #[test]
+    fn test_format_info() {
+        let lines = format_info(&Oracle {});
+        assert_eq!(lines[0], "name:  oracle");
+        assert_eq!(lines[1], "kind:  scraper");
+        assert_eq!(lines[2], "impl:  hotspot");
+        assert_eq!(lines[3], "home:  https://www.oracle.com/java/");
+        assert_eq!(lines[5], "auth:  not required");
+        assert_eq!(lines[6], "url:   https://www.oracle.com/java/technologies/downloads/");
+        assert!(lines.contains(
+            &"url:   https://www.oracle.com/java/technologies/javase/jdk17-archive-downloads.html".to_string()
+        ));
+        assert_eq!(lines.last().unwrap(), "note:  archives cover Java 17 and later only");
+    }
+}
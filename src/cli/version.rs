@@ -3,7 +3,7 @@ use std::sync::LazyLock;
 use eyre::Result;
 use versions::Versioning;
 
-use crate::{build_time::BUILD_TIME, env};
+use crate::{build_time::BUILD_TIME, env, jvm::SCHEMA_VERSION};
 
 /// Show version information
 #[derive(Debug, clap::Args)]
@@ -51,5 +51,6 @@ pub fn print_version_if_requested(args: &[String]) -> std::io::Result<()> {
 
 fn show_version() -> std::io::Result<()> {
     println!("{}", *VERSION);
+    println!("jvm data schema {SCHEMA_VERSION}");
     Ok(())
 }
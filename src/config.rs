@@ -1,16 +1,85 @@
 #![allow(unused)]
 
-use confique::{Config, Error};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use confique::Config;
+use eyre::Result;
+use serde::Serialize;
 use shellexpand::tilde;
 
-#[derive(Config, Debug)]
+#[derive(Config, Debug, Default, Serialize)]
 pub struct ExportConf {
     /// Path to the export directory
     #[config(env = "ROAST_EXPORT_PATH")]
     pub path: Option<String>,
+    /// OS to export musl Linux builds under instead of linux e.g. linux-musl
+    #[config(env = "ROAST_EXPORT_MUSL_OS")]
+    pub musl_os: Option<String>,
+    /// Names under which properties are exported e.g. java_version = "jdk_version"
+    #[config(env = "ROAST_EXPORT_RENAMES", parse_env = parse_map)]
+    pub renames: Option<HashMap<String, String>>,
+}
+
+#[derive(Config, Debug, Default, Serialize)]
+pub struct FetchConf {
+    /// Named groups of vendors e.g. lts = ["corretto", "temurin", "zulu"]
+    #[config(env = "ROAST_FETCH_VENDOR_GROUPS", parse_env = parse_groups)]
+    pub vendor_groups: Option<HashMap<String, Vec<String>>>,
+    /// Vendors fetched if none are given on the command line e.g. ["temurin", "zulu"]. Default: all
+    #[config(env = "ROAST_FETCH_DEFAULT_VENDORS", parse_env = parse_list)]
+    pub default_vendors: Option<Vec<String>>,
+    /// Fetch priorities overriding the ones of the vendors, higher ones are fetched first e.g. temurin = 20
+    #[config(env = "ROAST_FETCH_PRIORITIES", parse_env = parse_map)]
+    pub priorities: Option<HashMap<String, i32>>,
+    /// Optional features which are not recorded e.g. ["crac", "headless"]
+    #[config(env = "ROAST_FETCH_DISABLED_FEATURES", parse_env = parse_list)]
+    pub disabled_features: Option<Vec<String>>,
+    /// Image types which are recorded e.g. ["jdk"]. Default: all
+    #[config(env = "ROAST_FETCH_IMAGE_TYPES", parse_env = parse_list)]
+    pub image_types: Option<Vec<String>>,
+    /// Share of scraped records whose URL is checked before storing e.g. 0.1. Default: 0 (disabled)
+    #[config(env = "ROAST_FETCH_URL_CHECK_SAMPLE_RATE")]
+    pub url_check_sample_rate: Option<f64>,
+    /// Adoptium release pipeline crawled for temurin, one of ga, ea or nightly. Default: ga
+    #[config(env = "ROAST_FETCH_TEMURIN_CHANNEL")]
+    pub temurin_channel: Option<String>,
+    /// Handling of release candidate, beta and alpha builds, one of store (as ea with the prerelease feature) or drop.
+    /// Default: store
+    #[config(env = "ROAST_FETCH_PRERELEASES")]
+    pub prereleases: Option<String>,
+    /// User agent sent instead of the default one e.g. "Mozilla/5.0"
+    #[config(env = "ROAST_FETCH_USER_AGENT")]
+    pub user_agent: Option<String>,
+    /// Random share added to retry delays to spread out retries of parallel requests. Default: 0.5
+    #[config(env = "ROAST_FETCH_RETRY_JITTER")]
+    pub retry_jitter: Option<f64>,
+    /// Retries shared by all requests of a vendor before it fails without retrying. Default: 20
+    #[config(env = "ROAST_FETCH_RETRY_BUDGET")]
+    pub retry_budget: Option<usize>,
+    /// Cookies sent to vendors which list their downloads only after accepting a license e.g. oracle = "a=b"
+    #[config(env = "ROAST_FETCH_COOKIES", parse_env = parse_map)]
+    pub cookies: Option<HashMap<String, String>>,
+    /// Seconds a single request may take until it is aborted. Default: 30
+    #[config(env = "ROAST_FETCH_TIMEOUT_PER_URL")]
+    pub timeout_per_url: Option<u64>,
 }
 
-#[derive(Config, Debug)]
+#[derive(Config, Debug, Default, Serialize)]
+pub struct NormalizeConf {
+    /// Mappings of OS tokens to canonical names which take precedence over the built-in mapping e.g. illumos = "solaris"
+    #[config(env = "ROAST_NORMALIZE_OS", parse_env = parse_map)]
+    pub os: Option<HashMap<String, String>>,
+    /// Mappings of architecture tokens to canonical names which take precedence over the built-in mapping
+    #[config(env = "ROAST_NORMALIZE_ARCHITECTURE", parse_env = parse_map)]
+    pub architecture: Option<HashMap<String, String>>,
+}
+
+#[derive(Config, Debug, Default, Serialize)]
 pub struct DatabaseConf {
     /// Database connection pool size. Default: 10
     #[config(env = "ROAST_DATABASE_POOL_SIZE")]
@@ -32,21 +101,257 @@ pub struct DatabaseConf {
     pub ssl_key: Option<String>,
 }
 
-#[derive(Config, Debug)]
+#[derive(Config, Debug, Default, Serialize)]
 pub struct Conf {
     #[config(nested)]
     pub export: ExportConf,
     #[config(nested)]
     pub database: DatabaseConf,
+    #[config(nested)]
+    pub fetch: FetchConf,
+    #[config(nested)]
+    pub normalize: NormalizeConf,
 }
 
 impl Conf {
-    pub fn try_get() -> Result<Self, Error> {
-        let conf = Config::builder()
-            .env()
-            .file("config.toml")
-            .file(tilde("~/.config/roast/config.toml").into_owned())
+    pub fn try_get() -> Result<Self> {
+        if let Some(path) = explicit_path()
+            && !path.is_file()
+        {
+            return Err(eyre::eyre!("configuration file {} not found", path.display()));
+        }
+        Self::load(&Self::paths())
+    }
+
+    /// Loads the files in order of precedence, environment variables take precedence over all of them
+    fn load(paths: &[PathBuf]) -> Result<Self> {
+        let conf = paths
+            .iter()
+            .fold(Config::builder().env(), |builder, path| builder.file(path))
             .load()?;
         Ok(conf)
     }
+
+    /// Configuration files in order of precedence, missing ones are skipped
+    ///
+    /// The file given by `--config` or `ROAST_CONFIG` is the only one loaded, otherwise `./roast.toml`,
+    /// `./config.toml` and `$XDG_CONFIG_HOME/roast/config.toml` (`~/.config` if unset) are merged.
+    pub fn paths() -> Vec<PathBuf> {
+        search_paths(explicit_path(), Path::new("."), &config_home())
+    }
+}
+
+/// Returned if the value of an environment variable overriding a list or table can not be parsed
+#[derive(Debug)]
+pub struct ParseEnvError(String);
+
+impl Display for ParseEnvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseEnvError {}
+
+/// Parses a list separated by commas e.g. ROAST_FETCH_IMAGE_TYPES="jdk,jre"
+fn parse_list(value: &str) -> Result<Vec<String>, ParseEnvError> {
+    Ok(value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Parses entries of a table separated by commas e.g. ROAST_FETCH_PRIORITIES="temurin=20,zulu=10"
+fn parse_map<V: FromStr>(value: &str) -> Result<HashMap<String, V>, ParseEnvError> {
+    parse_entries(value, ',', |value| value.parse::<V>().ok())
+}
+
+/// Parses groups separated by semicolons e.g. ROAST_FETCH_VENDOR_GROUPS="lts=corretto,temurin;graal=mandrel"
+fn parse_groups(value: &str) -> Result<HashMap<String, Vec<String>>, ParseEnvError> {
+    parse_entries(value, ';', |value| parse_list(value).ok())
+}
+
+fn parse_entries<V, F>(value: &str, separator: char, parse: F) -> Result<HashMap<String, V>, ParseEnvError>
+where
+    F: Fn(&str) -> Option<V>,
+{
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once('=')
+                .and_then(|(key, value)| Some((key.trim().to_string(), parse(value.trim())?)))
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| ParseEnvError(format!("invalid entry {entry}, expected key=value")))
+        })
+        .collect()
+}
+
+/// Returns the configuration file given by `--config`, or else by `ROAST_CONFIG`
+fn explicit_path() -> Option<PathBuf> {
+    crate::env::CONFIG.read().unwrap().clone().or_else(|| {
+        std::env::var_os("ROAST_CONFIG")
+            .filter(|path| !path.is_empty())
+            .map(|path| PathBuf::from(tilde(&path.to_string_lossy()).into_owned()))
+    })
+}
+
+/// Returns the base directory of user configuration files
+fn config_home() -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(tilde("~/.config").into_owned()))
+}
+
+fn search_paths(explicit: Option<PathBuf>, dir: &Path, config_home: &Path) -> Vec<PathBuf> {
+    match explicit {
+        Some(path) => vec![path],
+        None => vec![
+            dir.join("roast.toml"),
+            dir.join("config.toml"),
+            config_home.join("roast").join("config.toml"),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_search_paths() {
+        let dir = TempDir::new("config");
+        let project = dir.join("project");
+        let config_home = dir.join("xdg");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(config_home.join("roast")).unwrap();
+        let write = |path: &Path, export_path: &str| {
+            std::fs::write(path, format!("[export]\npath = \"{export_path}\"\n")).unwrap();
+        };
+        write(&project.join("roast.toml"), "roast");
+        write(&project.join("config.toml"), "config");
+        std::fs::write(
+            config_home.join("roast/config.toml"),
+            "[export]\npath = \"xdg\"\nmusl_os = \"linux-musl\"\n",
+        )
+        .unwrap();
+        let load = |explicit| {
+            Conf::load(&search_paths(explicit, &project, &config_home))
+                .unwrap()
+                .export
+        };
+
+        // ./roast.toml takes precedence over ./config.toml, which takes precedence over the global file
+        let export = load(None);
+        assert_eq!(export.path.as_deref(), Some("roast"));
+        // settings missing from the files taking precedence are merged from the others
+        assert_eq!(export.musl_os.as_deref(), Some("linux-musl"));
+        std::fs::remove_file(project.join("roast.toml")).unwrap();
+        assert_eq!(load(None).path.as_deref(), Some("config"));
+        std::fs::remove_file(project.join("config.toml")).unwrap();
+        assert_eq!(load(None).path.as_deref(), Some("xdg"));
+
+        // an explicit file replaces all others
+        write(&project.join("roast.toml"), "roast");
+        let explicit = dir.join("explicit.toml");
+        write(&explicit, "explicit");
+        assert_eq!(
+            search_paths(Some(explicit.clone()), &project, &config_home),
+            vec![explicit.clone()]
+        );
+        let export = load(Some(explicit));
+        assert_eq!(export.path.as_deref(), Some("explicit"));
+        assert_eq!(export.musl_os, None);
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let dir = TempDir::new("config-env");
+        let path = dir.join("roast.toml");
+        std::fs::write(
+            &path,
+            "[fetch]\nimage_types = [\"jdk\"]\nretry_budget = 10\ntimeout_per_url = 60\n\
+             [fetch.priorities]\ntemurin = 20\n",
+        )
+        .unwrap();
+
+        let vars = [
+            ("ROAST_FETCH_IMAGE_TYPES", "jdk,jre"),
+            ("ROAST_FETCH_PRIORITIES", "zulu=10"),
+            ("ROAST_FETCH_RETRY_BUDGET", "5"),
+        ];
+        // SAFETY: no other test reads or writes these variables
+        unsafe { vars.iter().for_each(|(key, value)| std::env::set_var(key, value)) };
+        let conf = Conf::load(&[path]);
+        unsafe { vars.iter().for_each(|(key, _)| std::env::remove_var(key)) };
+
+        let fetch = conf.unwrap().fetch;
+        assert_eq!(fetch.image_types, Some(vec!["jdk".to_string(), "jre".to_string()]));
+        assert_eq!(fetch.priorities, Some(HashMap::from([("zulu".to_string(), 10)])));
+        assert_eq!(fetch.retry_budget, Some(5));
+        // settings without an environment variable set keep the value of the file
+        assert_eq!(fetch.timeout_per_url, Some(60));
+    }
+
+    #[test]
+    fn test_parse_env() {
+        assert_eq!(parse_list(" jdk, jre,").unwrap(), vec!["jdk", "jre"]);
+        assert_eq!(
+            parse_map::<i32>("temurin=20, zulu = -1").unwrap(),
+            HashMap::from([("temurin".to_string(), 20), ("zulu".to_string(), -1)])
+        );
+        assert_eq!(
+            parse_map::<String>("java_version=jdk_version").unwrap(),
+            HashMap::from([("java_version".to_string(), "jdk_version".to_string())])
+        );
+        assert_eq!(
+            parse_groups("lts=corretto,temurin; graal=mandrel").unwrap(),
+            HashMap::from([
+                ("lts".to_string(), vec!["corretto".to_string(), "temurin".to_string()]),
+                ("graal".to_string(), vec!["mandrel".to_string()]),
+            ])
+        );
+        assert!(parse_map::<i32>("temurin=high").is_err());
+        assert!(parse_map::<String>("temurin").is_err());
+        assert!(parse_map::<String>("=jdk_version").is_err());
+    }
+
+    #[test]
+    fn test_config_documents_env_vars() {
+        // every setting of config.toml names the environment variable overriding it
+        let config = include_str!("../config.toml");
+        let mut section = "";
+        let mut comments = vec![];
+        for line in config.lines() {
+            let expected = if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = header;
+                header.contains('.').then(|| header.replace('.', "_"))
+            } else if let Some((key, _)) = line.trim_start_matches('#').split_once(" = ")
+                && !line.starts_with("# ")
+                && !section.contains('.')
+            {
+                Some(format!("{section}_{key}"))
+            } else {
+                None
+            };
+            if let Some(expected) = expected {
+                let expected = format!("ROAST_{}", expected.to_uppercase());
+                let documented = comments
+                    .iter()
+                    .any(|comment: &&str| comment.split_whitespace().nth(1) == Some(expected.as_str()));
+                assert!(documented, "{line} is missing {expected}");
+            }
+            match line.starts_with("# ") {
+                true => comments.push(line),
+                false => comments.clear(),
+            }
+        }
+    }
 }
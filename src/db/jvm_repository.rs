@@ -1,14 +1,45 @@
 use std::collections::HashSet;
 
-use crate::jvm::JvmData;
+use crate::jvm::{JvmData, JvmImpl};
 use eyre::Result;
-use indoc::indoc;
+use indoc::{formatdoc, indoc};
+use itertools::Itertools;
+use log::warn;
 use postgres_openssl::MakeTlsConnector;
 use r2d2::Pool;
 use r2d2_postgres::PostgresConnectionManager;
 
 const BATCH_SIZE: usize = 1000;
 
+/// Whether the database contains any records to read
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DataState {
+    /// The JVM table does not exist yet
+    Uninitialized,
+    /// The JVM table exists but has no records
+    Empty,
+    Ready,
+}
+
+impl DataState {
+    fn from(table_exists: bool, has_rows: bool) -> Self {
+        match (table_exists, has_rows) {
+            (false, _) => DataState::Uninitialized,
+            (true, false) => DataState::Empty,
+            (true, true) => DataState::Ready,
+        }
+    }
+
+    /// Returns a hint for commands reading records, none if there are records
+    pub fn no_data_message(self) -> Option<&'static str> {
+        match self {
+            DataState::Uninitialized => Some("no data yet, apply sql/schema.sql and run `roast fetch` first"),
+            DataState::Empty => Some("no data yet, run `roast fetch` first"),
+            DataState::Ready => None,
+        }
+    }
+}
+
 pub struct JvmRepository {
     pool: Pool<PostgresConnectionManager<MakeTlsConnector>>,
 }
@@ -18,16 +49,38 @@ impl JvmRepository {
         Ok(JvmRepository { pool })
     }
 
+    /// Returns whether the JVM table exists and contains records, commands reading records print its
+    /// `no_data_message` instead of failing on a missing table
+    pub fn get_data_state(&self) -> Result<DataState> {
+        let mut conn = self.pool.get()?;
+        let table_exists: bool = conn.query_one("SELECT to_regclass('jvm') IS NOT NULL;", &[])?.get(0);
+        if !table_exists {
+            return Ok(DataState::from(false, false));
+        }
+        let has_rows: bool = conn.query_one("SELECT EXISTS (SELECT 1 FROM JVM);", &[])?.get(0);
+        Ok(DataState::from(true, has_rows))
+    }
+
+    /// Inserts new records and updates the changed fields of existing ones
     pub fn insert(&self, jvm_data: &HashSet<JvmData>) -> Result<u64> {
+        self.upsert(jvm_data, true)
+    }
+
+    /// Inserts new records and leaves existing ones untouched
+    pub fn insert_new(&self, jvm_data: &HashSet<JvmData>) -> Result<u64> {
+        self.upsert(jvm_data, false)
+    }
+
+    fn upsert(&self, jvm_data: &HashSet<JvmData>, merge: bool) -> Result<u64> {
         let mut conn = self.pool.get()?;
         let mut result = 0;
         let mut tx = conn.transaction()?;
-        let columns = 15;
+        let columns = 18;
 
         for chunk in map_workaround(jvm_data).chunks(BATCH_SIZE) {
             let mut query = String::from(
                 "INSERT INTO JVM
-                (architecture, checksum, checksum_url, features, file_type, filename, image_type, java_version, jvm_impl, os, release_type, size, url, vendor, version)
+                (architecture, build, checksum, checksum_url, checksums, features, file_type, filename, image_type, java_version, jvm_impl, os, release_type, resolved_url, size, url, vendor, version)
                 VALUES "
             );
 
@@ -37,26 +90,16 @@ impl JvmRepository {
                     query.push(',');
                 }
                 query.push_str(&format!(
-                    "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                    i * columns + 1,
-                    i * columns + 2,
-                    i * columns + 3,
-                    i * columns + 4,
-                    i * columns + 5,
-                    i * columns + 6,
-                    i * columns + 7,
-                    i * columns + 8,
-                    i * columns + 9,
-                    i * columns + 10,
-                    i * columns + 11,
-                    i * columns + 12,
-                    i * columns + 13,
-                    i * columns + 14,
-                    i * columns + 15
+                    "({})",
+                    (1..=columns)
+                        .map(|column| format!("${}", i * columns + column))
+                        .join(", ")
                 ));
                 params.push(&data.architecture);
+                params.push(&data.build);
                 params.push(&data.checksum);
                 params.push(&data.checksum_url);
+                params.push(&data.checksums);
                 params.push(&data.features);
                 params.push(&data.file_type);
                 params.push(&data.filename);
@@ -65,48 +108,14 @@ impl JvmRepository {
                 params.push(&data.jvm_impl);
                 params.push(&data.os);
                 params.push(&data.release_type);
+                params.push(&data.resolved_url);
                 params.push(&data.size);
                 params.push(&data.url);
                 params.push(&data.vendor);
                 params.push(&data.version);
             }
 
-            query.push_str(
-                " ON CONFLICT(url) DO UPDATE SET
-                architecture = excluded.architecture,
-                checksum = excluded.checksum,
-                checksum_url = excluded.checksum_url,
-                features = excluded.features,
-                file_type = excluded.file_type,
-                filename = excluded.filename,
-                image_type = excluded.image_type,
-                java_version = excluded.java_version,
-                jvm_impl = excluded.jvm_impl,
-                modified_at = CURRENT_TIMESTAMP,
-                os = excluded.os,
-                release_type = excluded.release_type,
-                size = excluded.size,
-                url = excluded.url,
-                vendor = excluded.vendor,
-                version = excluded.version
-                WHERE
-                   excluded.architecture != JVM.architecture
-                OR excluded.checksum != JVM.checksum
-                OR excluded.checksum_url != JVM.checksum_url
-                OR excluded.features != JVM.features
-                OR excluded.file_type != JVM.file_type
-                OR excluded.filename != JVM.filename
-                OR excluded.image_type != JVM.image_type
-                OR excluded.java_version != JVM.java_version
-                OR excluded.jvm_impl != JVM.jvm_impl
-                OR excluded.os != JVM.os
-                OR excluded.release_type != JVM.release_type
-                OR excluded.size != JVM.size
-                OR excluded.url != JVM.url
-                OR excluded.vendor != JVM.vendor
-                OR excluded.version != JVM.version
-                ;",
-            );
+            query.push_str(get_on_conflict_clause(merge));
 
             result += tx.execute(&query, &params)?;
         }
@@ -119,8 +128,10 @@ impl JvmRepository {
         let stmt = indoc! {
           "SELECT
               architecture,
+              build,
               checksum,
               checksum_url,
+              checksums,
               features,
               file_type,
               filename,
@@ -129,6 +140,7 @@ impl JvmRepository {
               jvm_impl,
               os,
               release_type,
+              resolved_url,
               size,
               url,
               vendor,
@@ -139,18 +151,23 @@ impl JvmRepository {
               release_type = $1
               AND os = $2
               AND architecture = $3
+          ORDER BY
+              url ASC
           ;",
         };
 
         self.export(stmt, &[&release_type, &os, &arch])
     }
 
+    /// Exports the records of a vendor, OS and architecture ordered by URL
     pub fn export_vendor(&self, vendor: &str, os: &str, arch: &str) -> Result<Vec<JvmData>> {
         let stmt = indoc::indoc! {
           "SELECT
               architecture,
+              build,
               checksum,
               checksum_url,
+              checksums,
               features,
               file_type,
               filename,
@@ -159,6 +176,7 @@ impl JvmRepository {
               jvm_impl,
               os,
               release_type,
+              resolved_url,
               size,
               url,
               vendor,
@@ -169,22 +187,79 @@ impl JvmRepository {
               vendor = $1
               AND os = $2
               AND architecture = $3
+          ORDER BY
+              url ASC
           ;"
         };
 
         self.export(stmt, &[&vendor, &os, &arch])
     }
 
+    /// Exports the data matching the given values of the given columns, all data if no columns are given
+    pub fn export_by(&self, columns: &[&str], values: &[String]) -> Result<Vec<JvmData>> {
+        let conditions = match columns.is_empty() {
+            true => "TRUE".to_string(),
+            false => columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| format!("{column} = ${}", i + 1))
+                .join(" AND "),
+        };
+        let stmt = formatdoc! {
+          "SELECT
+              architecture,
+              build,
+              checksum,
+              checksum_url,
+              checksums,
+              features,
+              file_type,
+              filename,
+              image_type,
+              java_version,
+              jvm_impl,
+              os,
+              release_type,
+              resolved_url,
+              size,
+              url,
+              vendor,
+              version
+          FROM
+              JVM
+          WHERE
+              {conditions}
+          ORDER BY
+              url ASC
+          ;"
+        };
+        let params = values
+            .iter()
+            .map(|v| v as &(dyn postgres::types::ToSql + Sync))
+            .collect::<Vec<_>>();
+
+        self.export(&stmt, &params)
+    }
+
     fn export(&self, query: &str, params: &[&(dyn postgres::types::ToSql + Sync)]) -> Result<Vec<JvmData>> {
         let mut conn = self.pool.get()?;
         let stmt = conn.prepare(query)?;
         let mut data = Vec::new();
         let rows = conn.query(&stmt, params)?;
         for row in rows {
+            let url: String = row.get("url");
+            let Some(jvm_impl) = parse_jvm_impl(&url, row.get("jvm_impl")) else {
+                continue;
+            };
             data.push(JvmData {
                 architecture: row.get("architecture"),
+                build: row.get("build"),
                 checksum: row.get("checksum"),
                 checksum_url: row.get("checksum_url"),
+                checksums: row
+                    .get::<_, Option<String>>("checksums")
+                    .map(|c| c.split(',').map(String::from).collect())
+                    .unwrap_or_default(),
                 features: row
                     .get::<_, Option<String>>("features")
                     .map(|f| f.split(',').map(String::from).collect()),
@@ -192,11 +267,12 @@ impl JvmRepository {
                 filename: row.get("filename"),
                 image_type: row.get("image_type"),
                 java_version: row.get("java_version"),
-                jvm_impl: row.get("jvm_impl"),
+                jvm_impl,
                 os: row.get("os"),
                 release_type: row.get("release_type"),
-                size: row.get::<_, Option<i32>>("size"),
-                url: row.get("url"),
+                resolved_url: row.get("resolved_url"),
+                size: row.get::<_, Option<i64>>("size"),
+                url,
                 vendor: row.get("vendor"),
                 version: row.get("version"),
             });
@@ -204,9 +280,164 @@ impl JvmRepository {
         Ok(data)
     }
 
+    /// Returns the distinct combinations of values of the given columns, skipping rows with missing values
+    pub fn get_distinct_combinations(&self, columns: &[&str]) -> Result<Vec<Vec<String>>> {
+        let mut conn = self.pool.get()?;
+        let columns = columns.join(", ");
+        let stmt = conn.prepare(&format!("SELECT DISTINCT {columns} FROM JVM ORDER BY {columns} ASC;"))?;
+        let mut data = Vec::new();
+        let rows = conn.query(&stmt, &[])?;
+        for row in rows {
+            let values = (0..row.len())
+                .map(|i| row.get::<usize, Option<String>>(i))
+                .collect::<Option<Vec<String>>>();
+            if let Some(values) = values {
+                data.push(values);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Returns the vendor and URL of records of a release type without any checksum
+    pub fn get_missing_checksums(&self, release_type: &str) -> Result<Vec<(String, String)>> {
+        let mut conn = self.pool.get()?;
+        let stmt = conn.prepare(indoc! {
+          "SELECT
+              vendor,
+              url
+          FROM
+              JVM
+          WHERE
+              release_type = $1
+              AND (checksum IS NULL OR checksum = '')
+              AND (checksums IS NULL OR checksums = '')
+          ORDER BY
+              vendor ASC,
+              url ASC
+          ;"
+        })?;
+        let mut data = Vec::new();
+        let rows = conn.query(&stmt, &[&release_type])?;
+        for row in rows {
+            data.push((row.get("vendor"), row.get("url")));
+        }
+        Ok(data)
+    }
+
+    /// Returns the key, vendor and URL of records sharing a checksum or download target with records of other vendors
+    pub fn get_cross_vendor_duplicates(&self, by: &str) -> Result<Vec<(String, String, String)>> {
+        let key = match by {
+            "checksum" => "NULLIF(checksum, '')",
+            // the URL is unique, redirected records of different vendors may still share the resolved URL
+            "url" => "COALESCE(resolved_url, url)",
+            _ => {
+                return Err(eyre::eyre!(
+                    "can not find duplicates by {by}, expected one of: checksum, url"
+                ));
+            }
+        };
+        let mut conn = self.pool.get()?;
+        let stmt = conn.prepare(&formatdoc! {
+          "SELECT
+              {key} AS key,
+              vendor,
+              url
+          FROM
+              JVM
+          WHERE
+              {key} IN (
+                  SELECT {key} FROM JVM WHERE {key} IS NOT NULL GROUP BY {key} HAVING COUNT(DISTINCT vendor) > 1
+              )
+          ORDER BY
+              key ASC,
+              vendor ASC,
+              url ASC
+          ;"
+        })?;
+        let mut data = Vec::new();
+        let rows = conn.query(&stmt, &[])?;
+        for row in rows {
+            data.push((row.get("key"), row.get("vendor"), row.get("url")));
+        }
+        Ok(data)
+    }
+
+    /// Returns the records with a checksum_url, only their url, checksum_url, checksum and checksums are read
+    pub fn get_checksum_urls(&self, vendors: &[String]) -> Result<Vec<JvmData>> {
+        let mut conn = self.pool.get()?;
+        let stmt = conn.prepare(indoc! {
+          "SELECT
+              url,
+              checksum_url,
+              checksum,
+              checksums
+          FROM
+              JVM
+          WHERE
+              checksum_url IS NOT NULL
+              AND checksum_url != ''
+              AND (cardinality($1::TEXT[]) = 0 OR vendor = ANY($1))
+          ORDER BY
+              url ASC
+          ;"
+        })?;
+        let mut data = Vec::new();
+        let rows = conn.query(&stmt, &[&vendors])?;
+        for row in rows {
+            data.push(JvmData {
+                checksum: row.get("checksum"),
+                checksum_url: row.get("checksum_url"),
+                checksums: row
+                    .get::<_, Option<String>>("checksums")
+                    .map(|c| c.split(',').map(String::from).collect())
+                    .unwrap_or_default(),
+                url: row.get("url"),
+                ..Default::default()
+            });
+        }
+        Ok(data)
+    }
+
+    /// Updates the checksum and checksums of a record together so that they do not disagree
+    pub fn update_checksums(&self, url: &str, checksum: Option<&str>, checksums: &[String]) -> Result<u64> {
+        let mut conn = self.pool.get()?;
+        let checksums = (!checksums.is_empty()).then(|| checksums.join(","));
+        let result = conn.execute(
+            indoc! {
+              "UPDATE
+                  JVM
+              SET
+                  checksum = $2,
+                  checksums = $3,
+                  modified_at = CURRENT_TIMESTAMP
+              WHERE
+                  url = $1
+                  AND (checksum IS DISTINCT FROM $2 OR checksums IS DISTINCT FROM $3)
+              ;"
+            },
+            &[&url, &checksum, &checksums],
+        )?;
+        Ok(result)
+    }
+
+    pub fn get_distinct_by_vendor(&self, column: &str, vendor: &str) -> Result<Vec<String>> {
+        let mut conn = self.pool.get()?;
+        let stmt = conn.prepare(&format!(
+            "SELECT DISTINCT {column} FROM JVM WHERE vendor = $1 AND {column} IS NOT NULL ORDER BY {column} ASC;"
+        ))?;
+        let mut data = Vec::new();
+        let rows = conn.query(&stmt, &[&vendor])?;
+        for row in rows {
+            data.push(row.get::<usize, String>(0));
+        }
+        Ok(data)
+    }
+
     pub fn get_distinct(&self, column: &str) -> Result<Vec<String>> {
         let mut conn = self.pool.get()?;
-        let stmt = conn.prepare(&format!("SELECT DISTINCT {column} FROM JVM ORDER BY {column} ASC;"))?;
+        let stmt = conn.prepare(&format!(
+            "SELECT DISTINCT {column} FROM JVM WHERE {column} IS NOT NULL ORDER BY {column} ASC;"
+        ))?;
         let mut data = Vec::new();
         let rows = conn.query(&stmt, &[])?;
         for row in rows {
@@ -219,8 +450,10 @@ impl JvmRepository {
 #[derive(Clone, Default, Debug)]
 struct DbJvmData {
     pub architecture: String,
+    pub build: Option<String>,
     pub checksum: Option<String>,
     pub checksum_url: Option<String>,
+    pub checksums: Option<String>,
     pub features: Option<String>,
     pub file_type: String,
     pub filename: String,
@@ -229,30 +462,83 @@ struct DbJvmData {
     pub jvm_impl: String,
     pub os: String,
     pub release_type: String,
-    pub size: Option<i32>,
+    pub resolved_url: Option<String>,
+    pub size: Option<i64>,
     pub url: String,
     pub vendor: String,
     pub version: String,
 }
 
+/// Returns the conflict clause of the insert, existing records are only updated if `merge` is set
+fn get_on_conflict_clause(merge: bool) -> &'static str {
+    match merge {
+        true => {
+            " ON CONFLICT(url) DO UPDATE SET
+                architecture = excluded.architecture,
+                build = excluded.build,
+                checksum = excluded.checksum,
+                checksum_url = excluded.checksum_url,
+                checksums = excluded.checksums,
+                features = excluded.features,
+                file_type = excluded.file_type,
+                filename = excluded.filename,
+                image_type = excluded.image_type,
+                java_version = excluded.java_version,
+                jvm_impl = excluded.jvm_impl,
+                modified_at = CURRENT_TIMESTAMP,
+                os = excluded.os,
+                release_type = excluded.release_type,
+                resolved_url = excluded.resolved_url,
+                size = excluded.size,
+                url = excluded.url,
+                vendor = excluded.vendor,
+                version = excluded.version
+                WHERE
+                   excluded.architecture IS DISTINCT FROM JVM.architecture
+                OR excluded.build IS DISTINCT FROM JVM.build
+                OR excluded.checksum IS DISTINCT FROM JVM.checksum
+                OR excluded.checksum_url IS DISTINCT FROM JVM.checksum_url
+                OR excluded.checksums IS DISTINCT FROM JVM.checksums
+                OR excluded.features IS DISTINCT FROM JVM.features
+                OR excluded.file_type IS DISTINCT FROM JVM.file_type
+                OR excluded.filename IS DISTINCT FROM JVM.filename
+                OR excluded.image_type IS DISTINCT FROM JVM.image_type
+                OR excluded.java_version IS DISTINCT FROM JVM.java_version
+                OR excluded.jvm_impl IS DISTINCT FROM JVM.jvm_impl
+                OR excluded.os IS DISTINCT FROM JVM.os
+                OR excluded.release_type IS DISTINCT FROM JVM.release_type
+                OR excluded.resolved_url IS DISTINCT FROM JVM.resolved_url
+                OR excluded.size IS DISTINCT FROM JVM.size
+                OR excluded.url IS DISTINCT FROM JVM.url
+                OR excluded.vendor IS DISTINCT FROM JVM.vendor
+                OR excluded.version IS DISTINCT FROM JVM.version
+                ;"
+        }
+        false => " ON CONFLICT(url) DO NOTHING;",
+    }
+}
+
 fn map_workaround(jvm_data: &HashSet<JvmData>) -> Vec<DbJvmData> {
     jvm_data
         .iter()
-        // workaround for the `feature` field which needs to be joined
+        // workaround for the `checksums` and `features` fields which need to be joined
         // and therefore would not live long enough in context of a
         // batch insert
         .map(|item| DbJvmData {
             architecture: item.architecture.clone(),
+            build: item.build.clone(),
             checksum: item.checksum.clone(),
             checksum_url: item.checksum_url.clone(),
+            checksums: (!item.checksums.is_empty()).then(|| item.checksums.join(",")),
             features: item.features.as_ref().map(|f| f.join(",")),
             file_type: item.file_type.clone(),
             filename: item.filename.clone(),
             image_type: item.image_type.clone(),
             java_version: item.java_version.clone(),
-            jvm_impl: item.jvm_impl.clone(),
+            jvm_impl: item.jvm_impl.to_string(),
             os: item.os.clone(),
             release_type: item.release_type.clone(),
+            resolved_url: item.resolved_url.clone(),
             size: item.size,
             url: item.url.clone(),
             vendor: item.vendor.clone(),
@@ -260,3 +546,56 @@ fn map_workaround(jvm_data: &HashSet<JvmData>) -> Vec<DbJvmData> {
         })
         .collect::<Vec<DbJvmData>>()
 }
+
+/// Parses a stored JVM implementation, unknown ones are skipped with a warning instead of failing the whole export
+fn parse_jvm_impl(url: &str, value: Option<String>) -> Option<JvmImpl> {
+    match value.as_deref().map(str::parse::<JvmImpl>) {
+        Some(Ok(jvm_impl)) => Some(jvm_impl),
+        Some(Err(err)) => {
+            warn!("skipping {url}: {err}");
+            None
+        }
+        None => {
+            warn!("skipping {url}: missing jvm_impl");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jvm_impl() {
+        let url = "https://example.com/jdk.tar.gz";
+        assert_eq!(parse_jvm_impl(url, Some("zing".to_string())), Some(JvmImpl::Zing));
+        assert_eq!(parse_jvm_impl(url, Some("hotspot".to_string())), Some(JvmImpl::Hotspot));
+        assert_eq!(parse_jvm_impl(url, Some("unknown".to_string())), None);
+        assert_eq!(parse_jvm_impl(url, None), None);
+    }
+
+    #[test]
+    fn test_data_state() {
+        let uninitialized = DataState::from(false, false);
+        assert_eq!(uninitialized, DataState::Uninitialized);
+        assert!(uninitialized.no_data_message().unwrap().contains("roast fetch"));
+
+        let empty = DataState::from(true, false);
+        assert_eq!(empty, DataState::Empty);
+        assert!(empty.no_data_message().unwrap().contains("roast fetch"));
+
+        assert_eq!(DataState::from(true, true).no_data_message(), None);
+    }
+
+    #[test]
+    fn test_get_on_conflict_clause() {
+        let merge = get_on_conflict_clause(true);
+        assert!(merge.contains("ON CONFLICT(url) DO UPDATE SET"));
+        assert!(merge.contains("checksum = excluded.checksum,"));
+        assert!(merge.contains("OR excluded.checksum IS DISTINCT FROM JVM.checksum"));
+
+        let insert_only = get_on_conflict_clause(false);
+        assert_eq!(insert_only, " ON CONFLICT(url) DO NOTHING;");
+    }
+}
@@ -0,0 +1,67 @@
+use eyre::{Result, WrapErr};
+use postgres_openssl::MakeTlsConnector;
+use r2d2::Pool;
+use r2d2_postgres::PostgresConnectionManager;
+
+/// Tables created by ./sql/schema.sql
+pub const TABLES: [&str; 3] = ["jvm", "vendor_runs", "schema_migrations"];
+
+pub struct MaintenanceRepository {
+    pool: Pool<PostgresConnectionManager<MakeTlsConnector>>,
+}
+
+impl MaintenanceRepository {
+    pub fn new(pool: Pool<PostgresConnectionManager<MakeTlsConnector>>) -> Result<Self> {
+        Ok(MaintenanceRepository { pool })
+    }
+
+    /// Vacuums and analyzes a table, a full vacuum rewrites the table and returns the reclaimed space to the OS
+    ///
+    /// Requires the user to own the table, PostgreSQL skips it with a warning otherwise.
+    pub fn vacuum(&self, table: &str, full: bool) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        let options = match full {
+            true => "FULL, ANALYZE",
+            false => "ANALYZE",
+        };
+        conn.batch_execute(&format!("VACUUM ({options}) {table};"))
+            .wrap_err_with(|| format!("failed to vacuum {table}"))?;
+        Ok(())
+    }
+
+    /// Returns the size of a table including its indexes in bytes, none if the table does not exist
+    pub fn get_size(&self, table: &str) -> Result<Option<i64>> {
+        let mut conn = self.pool.get()?;
+        let size = conn
+            .query_one("SELECT pg_total_relation_size(to_regclass($1));", &[&table])?
+            .get(0);
+        Ok(size)
+    }
+
+    /// Returns the tables of ./sql/schema.sql which do not exist
+    pub fn get_missing_tables(&self) -> Result<Vec<String>> {
+        let mut conn = self.pool.get()?;
+        let mut missing = Vec::new();
+        for table in TABLES {
+            let exists: bool = conn.query_one("SELECT to_regclass($1) IS NOT NULL;", &[&table])?.get(0);
+            if !exists {
+                missing.push(table.to_string());
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Returns the indexes of the tables which are invalid or not ready e.g. after an interrupted index build
+    pub fn get_invalid_indexes(&self) -> Result<Vec<String>> {
+        let mut conn = self.pool.get()?;
+        let tables = TABLES.to_vec();
+        let rows = conn.query(
+            "SELECT indexrelid::regclass::text FROM pg_index
+             WHERE indrelid IN (SELECT to_regclass(t) FROM unnest($1::text[]) AS t)
+             AND NOT (indisvalid AND indisready)
+             ORDER BY 1;",
+            &[&tables],
+        )?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+}
@@ -0,0 +1,96 @@
+use eyre::{Result, WrapErr};
+use indoc::indoc;
+use postgres_openssl::MakeTlsConnector;
+use r2d2::Pool;
+use r2d2_postgres::PostgresConnectionManager;
+
+/// Schema change of ./sql/migrations, applied in order of the version
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// All migrations, new ones are appended and inserted into SCHEMA_MIGRATIONS of ./sql/schema.sql as well
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "add_checksums",
+        sql: include_str!("../../sql/migrations/0001_add_checksums.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "add_resolved_url",
+        sql: include_str!("../../sql/migrations/0002_add_resolved_url.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "add_vendor_runs",
+        sql: include_str!("../../sql/migrations/0003_add_vendor_runs.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "widen_size",
+        sql: include_str!("../../sql/migrations/0004_widen_size.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "add_schema_migrations",
+        sql: include_str!("../../sql/migrations/0005_add_schema_migrations.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "add_build",
+        sql: include_str!("../../sql/migrations/0006_add_build.sql"),
+    },
+];
+
+/// Creates the table recording applied migrations before the migration adding it was applied
+const CREATE_TABLE: &str = indoc! {
+  "CREATE TABLE IF NOT EXISTS SCHEMA_MIGRATIONS (
+      version INTEGER NOT NULL,
+      applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+      PRIMARY KEY(version)
+  );"
+};
+
+pub struct MigrationRepository {
+    pool: Pool<PostgresConnectionManager<MakeTlsConnector>>,
+}
+
+impl MigrationRepository {
+    pub fn new(pool: Pool<PostgresConnectionManager<MakeTlsConnector>>) -> Result<Self> {
+        Ok(MigrationRepository { pool })
+    }
+
+    /// Returns the version of the last applied migration, 0 if the database was never migrated
+    pub fn get_version(&self) -> Result<u32> {
+        let mut conn = self.pool.get()?;
+        let table_exists: bool = conn
+            .query_one("SELECT to_regclass('schema_migrations') IS NOT NULL;", &[])?
+            .get(0);
+        if !table_exists {
+            return Ok(0);
+        }
+        let version: Option<i32> = conn
+            .query_one("SELECT MAX(version) FROM SCHEMA_MIGRATIONS;", &[])?
+            .get(0);
+        Ok(version.unwrap_or_default() as u32)
+    }
+
+    /// Applies the migration and records its version in a single transaction
+    pub fn apply(&self, migration: &Migration) -> Result<()> {
+        let mut conn = self.pool.get()?;
+        let mut tx = conn.transaction()?;
+        tx.batch_execute(CREATE_TABLE)?;
+        tx.batch_execute(migration.sql)
+            .wrap_err_with(|| format!("failed to apply migration {}", migration.name))?;
+        tx.execute(
+            "INSERT INTO SCHEMA_MIGRATIONS (version) VALUES ($1) ON CONFLICT(version) DO NOTHING;",
+            &[&(migration.version as i32)],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -1,2 +1,5 @@
 pub mod jvm_repository;
+pub mod maintenance_repository;
+pub mod migration_repository;
 pub mod pool;
+pub mod vendor_run_repository;
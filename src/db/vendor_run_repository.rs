@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use eyre::Result;
+use indoc::indoc;
+use postgres_openssl::MakeTlsConnector;
+use r2d2::Pool;
+use r2d2_postgres::PostgresConnectionManager;
+use serde::Serialize;
+
+/// Outcome of the last fetch of a vendor
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct VendorRun {
+    /// Key of the summary printed by fetch --json
+    #[serde(skip)]
+    pub vendor: String,
+    /// Set by the database when the run is saved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<String>,
+    pub duration_ms: i64,
+    /// Number of records fetched from the vendor
+    pub fetched: i64,
+    /// Number of records inserted or modified in the database
+    pub modified: i64,
+    pub status: String,
+    pub error: Option<String>,
+    /// Number of GitHub release pages fetched, none for vendors not listing GitHub releases, not stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages: Option<i64>,
+}
+
+impl VendorRun {
+    pub fn success(vendor: &str, duration: Duration, fetched: usize, modified: u64) -> Self {
+        VendorRun {
+            vendor: vendor.to_string(),
+            fetched_at: None,
+            duration_ms: duration.as_millis() as i64,
+            fetched: fetched as i64,
+            modified: modified as i64,
+            status: "success".to_string(),
+            error: None,
+            pages: None,
+        }
+    }
+
+    pub fn failure(vendor: &str, duration: Duration, fetched: usize, error: &eyre::Report) -> Self {
+        VendorRun {
+            vendor: vendor.to_string(),
+            fetched_at: None,
+            duration_ms: duration.as_millis() as i64,
+            fetched: fetched as i64,
+            modified: 0,
+            status: "failure".to_string(),
+            error: Some(error.to_string()),
+            pages: None,
+        }
+    }
+
+    /// Sets the number of GitHub release pages fetched
+    pub fn with_pages(mut self, pages: Option<usize>) -> Self {
+        self.pages = pages.map(|pages| pages as i64);
+        self
+    }
+}
+
+pub struct VendorRunRepository {
+    pool: Pool<PostgresConnectionManager<MakeTlsConnector>>,
+}
+
+impl VendorRunRepository {
+    pub fn new(pool: Pool<PostgresConnectionManager<MakeTlsConnector>>) -> Result<Self> {
+        Ok(VendorRunRepository { pool })
+    }
+
+    /// Saves the run as the last run of its vendor
+    pub fn save(&self, run: &VendorRun) -> Result<u64> {
+        let mut conn = self.pool.get()?;
+        let stmt = indoc! {
+          "INSERT INTO VENDOR_RUNS
+              (vendor, duration_ms, fetched, modified, status, error)
+          VALUES
+              ($1, $2, $3, $4, $5, $6)
+          ON CONFLICT(vendor) DO UPDATE SET
+              fetched_at = CURRENT_TIMESTAMP,
+              duration_ms = excluded.duration_ms,
+              fetched = excluded.fetched,
+              modified = excluded.modified,
+              status = excluded.status,
+              error = excluded.error
+          ;"
+        };
+        let result = conn.execute(
+            stmt,
+            &[
+                &run.vendor,
+                &run.duration_ms,
+                &run.fetched,
+                &run.modified,
+                &run.status,
+                &run.error,
+            ],
+        )?;
+        Ok(result)
+    }
+
+    pub fn find_all(&self) -> Result<Vec<VendorRun>> {
+        let mut conn = self.pool.get()?;
+        let stmt = indoc! {
+          "SELECT
+              vendor,
+              fetched_at,
+              duration_ms,
+              fetched,
+              modified,
+              status,
+              error
+          FROM
+              VENDOR_RUNS
+          ORDER BY
+              vendor ASC
+          ;"
+        };
+        let mut data = Vec::new();
+        for row in conn.query(stmt, &[])? {
+            data.push(VendorRun {
+                vendor: row.get("vendor"),
+                fetched_at: row.get("fetched_at"),
+                duration_ms: row.get("duration_ms"),
+                fetched: row.get("fetched"),
+                modified: row.get("modified"),
+                status: row.get("status"),
+                error: row.get("error"),
+                pages: None,
+            });
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_run() {
+        let run = VendorRun::success("zulu", Duration::from_millis(1500), 120, 7);
+        assert_eq!(run.vendor, "zulu");
+        assert_eq!(run.duration_ms, 1500);
+        assert_eq!(run.fetched, 120);
+        assert_eq!(run.modified, 7);
+        assert_eq!(run.status, "success");
+        assert!(run.error.is_none());
+
+        let run = VendorRun::failure("zulu", Duration::from_secs(2), 120, &eyre::eyre!("connection reset"));
+        assert_eq!(run.duration_ms, 2000);
+        assert_eq!(run.fetched, 120);
+        assert_eq!(run.modified, 0);
+        assert_eq!(run.status, "failure");
+        assert_eq!(run.error.as_deref(), Some("connection reset"));
+    }
+}
@@ -1,10 +1,16 @@
 use std::{
-    path,
+    path::{self, PathBuf},
     sync::{LazyLock, RwLock},
 };
 
 pub static ARGS: RwLock<Vec<String>> = RwLock::new(vec![]);
 
+/// Configuration file loaded instead of searching for one, takes precedence over ROAST_CONFIG
+pub static CONFIG: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Database connection URL overriding database.url for a single invocation
+pub static DATABASE_URL: RwLock<Option<String>> = RwLock::new(None);
+
 pub static ARGV0: LazyLock<String> = LazyLock::new(|| ARGS.read().unwrap()[0].to_string());
 
 pub static BINARY_NAME: LazyLock<&str> = LazyLock::new(|| filename(&ARGV0));
@@ -1,11 +1,24 @@
-use log::error;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use log::{error, info, warn};
 use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 use xx::regex;
 
-use crate::http::HTTP;
+use crate::{
+    http::RetryAfterError,
+    jvm::vendor::{FetchOptions, VendorError},
+};
 use eyre::Result;
 
+/// Maximum number of retries of a page after GitHub asked to back off
+const MAX_RETRIES: usize = 3;
+/// Upper bound for a single back off
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubRelease {
     pub assets: Vec<GitHubAsset>,
@@ -23,26 +36,142 @@ pub struct GitHubAsset {
     pub size: u64,
 }
 
-pub fn list_releases(repo: &str) -> Result<Vec<GitHubRelease>> {
-    let url = format!("https://api.github.com/repos/{repo}/releases?per_page=100");
+/// Number of retries which may be spent by the parallel requests of a vendor
+#[derive(Debug)]
+pub struct RetryBudget {
+    max: usize,
+    used: AtomicUsize,
+}
+
+impl RetryBudget {
+    pub fn new(max: usize) -> Self {
+        RetryBudget {
+            max,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Takes a retry from the budget, false if it is exhausted
+    fn try_take(&self) -> bool {
+        self.used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+                (used < self.max).then_some(used + 1)
+            })
+            .is_ok()
+    }
+}
+
+/// Returns the URL of the releases endpoint of a repository
+pub fn releases_url(repo: &str) -> String {
+    format!("https://api.github.com/repos/{repo}/releases")
+}
+
+/// Lists the releases of a repository, retries are taken from the retry budget of the vendor
+pub fn list_releases(options: &FetchOptions, vendor: &str, repo: &str) -> Result<Vec<GitHubRelease>> {
+    let url = format!("{}?per_page=100", releases_url(repo));
+
+    let budget = options.retry_budget(vendor);
+    let (mut releases, pages) = paginate(&url, options.max_pages, &budget, options.retry_jitter, |url| {
+        options.http.get_json_with_headers::<Vec<GitHubRelease>, _>(url)
+    })?;
+    info!("[{repo}] fetched {} releases from {pages} pages", releases.len());
+    options.add_pages(vendor, pages);
+    releases.retain(|r| !r.draft);
+
+    Ok(releases)
+}
 
-    let (mut releases, mut headers) = HTTP.get_json_with_headers::<Vec<GitHubRelease>, _>(url)?;
+/// Fetches all pages following the `link` header and returns the items along with the number of fetched pages
+///
+/// Stops after `max_pages` pages if given, GitHub returns the most recent releases first. A failing page ends the
+/// listing unless the retry budget is exhausted, which fails it.
+fn paginate<T, F>(
+    url: &str,
+    max_pages: Option<usize>,
+    budget: &RetryBudget,
+    jitter: f64,
+    fetch: F,
+) -> Result<(Vec<T>, usize)>
+where
+    F: Fn(&str) -> Result<(Vec<T>, HeaderMap)>,
+{
+    let (mut items, mut headers) = fetch_with_backoff(url, budget, jitter, &fetch)?;
+    let mut pages = 1;
 
     while let Some(next) = next_page(&headers) {
-        let (more, h) = match HTTP.get_json_with_headers::<Vec<GitHubRelease>, _>(&next) {
+        if max_pages.is_some_and(|max_pages| pages >= max_pages) {
+            info!("stopping after {pages} pages of {url}");
+            break;
+        }
+        let (more, h) = match fetch_with_backoff(&next, budget, jitter, &fetch) {
             Ok(result) => result,
+            Err(err) if is_budget_exhausted(&err) => return Err(err),
             Err(err) => {
                 // GitHub API returns 422 if more than 1000 releases are requested
                 error!("failed to fetch release page: {err}");
                 break;
             }
         };
-        releases.extend(more);
+        items.extend(more);
         headers = h;
+        pages += 1;
     }
-    releases.retain(|r| !r.draft);
 
-    Ok(releases)
+    Ok((items, pages))
+}
+
+/// Fetches a page and retries it if GitHub responds with a `Retry-After` (abuse detection/secondary rate limit)
+///
+/// Each retry is taken from `budget`, once it is exhausted the page fails without waiting. Delays are increased by a
+/// random share of up to `jitter`.
+fn fetch_with_backoff<T, F>(url: &str, budget: &RetryBudget, jitter: f64, fetch: &F) -> Result<(Vec<T>, HeaderMap)>
+where
+    F: Fn(&str) -> Result<(Vec<T>, HeaderMap)>,
+{
+    let mut retries = 0;
+    loop {
+        match fetch(url) {
+            Err(err) if retries < MAX_RETRIES => match err.downcast_ref::<RetryAfterError>() {
+                Some(_) if !budget.try_take() => {
+                    return Err(err.wrap_err(VendorError::RetryBudgetExhausted {
+                        max: budget.max,
+                        url: url.to_string(),
+                    }));
+                }
+                Some(retry) => {
+                    let delay = backoff_delay(retries, retry.retry_after, jitter, rand::random::<f64>());
+                    warn!(
+                        "GitHub asked to back off, retrying {url} in {:.1} seconds",
+                        delay.as_secs_f64()
+                    );
+                    std::thread::sleep(delay);
+                    retries += 1;
+                }
+                None => return Err(err),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Returns the delay before retry number `attempt` (starting at 0)
+///
+/// Starts with the `Retry-After` of the host and doubles with each attempt, a random share of up to `jitter` (using
+/// `random` in `[0, 1)`) is added on top. The delay never exceeds [MAX_RETRY_AFTER].
+fn backoff_delay(attempt: usize, retry_after: Duration, jitter: f64, random: f64) -> Duration {
+    // clamped before the jitter as well, so huge `Retry-After` values can not overflow
+    retry_after
+        .saturating_mul(2u32.saturating_pow(attempt as u32))
+        .min(MAX_RETRY_AFTER)
+        .mul_f64(1.0 + jitter * random)
+        .min(MAX_RETRY_AFTER)
+}
+
+fn is_budget_exhausted(err: &eyre::Report) -> bool {
+    matches!(
+        err.downcast_ref::<VendorError>(),
+        Some(VendorError::RetryBudgetExhausted { .. })
+    )
 }
 
 fn next_page(headers: &HeaderMap) -> Option<String> {
@@ -54,3 +183,219 @@ fn next_page(headers: &HeaderMap) -> Option<String> {
         .captures(&link)
         .map(|c| c.get(1).unwrap().as_str().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        let jitter = 0.5;
+        for (attempt, retry_after, min) in [
+            (0, Duration::from_secs(10), Duration::from_secs(10)),
+            (1, Duration::from_secs(10), Duration::from_secs(20)),
+            (2, Duration::from_secs(10), Duration::from_secs(40)),
+            (3, Duration::from_secs(10), Duration::from_secs(80)),
+            (4, Duration::from_secs(10), MAX_RETRY_AFTER),
+            (0, Duration::from_secs(600), MAX_RETRY_AFTER),
+        ] {
+            let max = min.mul_f64(1.0 + jitter).min(MAX_RETRY_AFTER);
+            for random in [0.0, 0.25, 0.5, 0.999] {
+                let delay = backoff_delay(attempt, retry_after, jitter, random);
+                assert!(
+                    delay >= min && delay <= max,
+                    "attempt {attempt}: {delay:?} not in {min:?}..={max:?}"
+                );
+            }
+        }
+        assert_eq!(
+            backoff_delay(2, Duration::from_secs(10), 0.0, 0.999),
+            Duration::from_secs(40)
+        );
+        assert_eq!(backoff_delay(2, Duration::ZERO, jitter, 0.999), Duration::ZERO);
+        // the jitter does not push the delay beyond the maximum
+        assert_eq!(
+            backoff_delay(0, Duration::from_secs(100), jitter, 0.999),
+            MAX_RETRY_AFTER
+        );
+    }
+
+    fn retry_after() -> eyre::Report {
+        RetryAfterError {
+            url: "https://api.github.com/repos/test/releases".to_string(),
+            status: 403,
+            retry_after: Duration::ZERO,
+        }
+        .into()
+    }
+
+    fn unlimited() -> RetryBudget {
+        RetryBudget::new(usize::MAX)
+    }
+
+    #[test]
+    fn test_paginate_with_retry_after() {
+        let calls = Cell::new(0);
+        let (items, pages) = paginate(
+            "https://api.github.com/repos/test/releases?page=1",
+            None,
+            &unlimited(),
+            0.0,
+            |url| {
+                calls.set(calls.get() + 1);
+                match (calls.get(), url) {
+                    (1, _) => Err(retry_after()),
+                    (2, "https://api.github.com/repos/test/releases?page=1") => {
+                        let mut headers = HeaderMap::new();
+                        headers.insert(
+                            "link",
+                            HeaderValue::from_static(
+                                r#"<https://api.github.com/repos/test/releases?page=2>; rel="next""#,
+                            ),
+                        );
+                        Ok((vec![1], headers))
+                    }
+                    (3, _) => Err(retry_after()),
+                    (4, "https://api.github.com/repos/test/releases?page=2") => Ok((vec![2], HeaderMap::new())),
+                    _ => Err(eyre::eyre!("unexpected request {url}")),
+                }
+            },
+        )
+        .unwrap();
+
+        assert_eq!(items, vec![1, 2]);
+        assert_eq!(pages, 2);
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_paginate_gives_up_after_max_retries() {
+        let calls = Cell::new(0);
+        let result = paginate::<u8, _>(
+            "https://api.github.com/repos/test/releases",
+            None,
+            &unlimited(),
+            0.0,
+            |_| {
+                calls.set(calls.get() + 1);
+                Err(retry_after())
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(calls.get(), MAX_RETRIES + 1);
+    }
+
+    #[test]
+    fn test_paginate_stops_retrying_after_budget() {
+        let budget = RetryBudget::new(2);
+        let calls = Cell::new(0);
+        let fetch = |_: &str| {
+            calls.set(calls.get() + 1);
+            Err::<(Vec<u8>, HeaderMap), _>(retry_after())
+        };
+
+        let err = paginate(
+            "https://api.github.com/repos/test/a/releases",
+            None,
+            &budget,
+            0.0,
+            fetch,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("retry budget of 2 exhausted"), "{err}");
+        assert_eq!(calls.get(), 3);
+
+        // further repositories of the vendor fail on their first attempt
+        calls.set(0);
+        assert!(
+            paginate(
+                "https://api.github.com/repos/test/b/releases",
+                None,
+                &budget,
+                0.0,
+                fetch
+            )
+            .is_err()
+        );
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_paginate_fails_after_budget_past_first_page() {
+        let fetch = |url: &str, err: fn() -> eyre::Report| match url {
+            "https://api.github.com/repos/test/releases?page=1" => {
+                let mut headers = HeaderMap::new();
+                headers.insert(
+                    "link",
+                    HeaderValue::from_static(r#"<https://api.github.com/repos/test/releases?page=2>; rel="next""#),
+                );
+                Ok((vec![1], headers))
+            }
+            _ => Err(err()),
+        };
+
+        // the exhausted budget fails the listing instead of returning the first page only
+        let err = paginate(
+            "https://api.github.com/repos/test/releases?page=1",
+            None,
+            &RetryBudget::new(1),
+            0.0,
+            |url| fetch(url, retry_after),
+        )
+        .unwrap_err();
+        assert!(is_budget_exhausted(&err), "{err}");
+
+        // other failures end the listing
+        let (items, pages) = paginate(
+            "https://api.github.com/repos/test/releases?page=1",
+            None,
+            &RetryBudget::new(1),
+            0.0,
+            |url| fetch(url, || eyre::eyre!("422 Unprocessable Entity")),
+        )
+        .unwrap();
+        assert_eq!(items, vec![1]);
+        assert_eq!(pages, 1);
+    }
+
+    #[test]
+    fn test_retry_budget() {
+        let budget = RetryBudget::new(2);
+        assert!(budget.try_take());
+        assert!(budget.try_take());
+        assert!(!budget.try_take());
+    }
+
+    #[test]
+    fn test_paginate_with_max_pages() {
+        let calls = Cell::new(0);
+        let fetch = |url: &str| {
+            calls.set(calls.get() + 1);
+            let page = url.rsplit('=').next().unwrap().parse::<u8>().unwrap();
+            let mut headers = HeaderMap::new();
+            let next = format!(
+                "<https://api.github.com/repos/test/releases?page={}>; rel=\"next\"",
+                page + 1
+            );
+            headers.insert("link", HeaderValue::from_str(&next).unwrap());
+            Ok((vec![page], headers))
+        };
+
+        let (items, pages) = paginate(
+            "https://api.github.com/repos/test/releases?page=1",
+            Some(3),
+            &unlimited(),
+            0.0,
+            fetch,
+        )
+        .unwrap();
+        assert_eq!(items, vec![1, 2, 3]);
+        assert_eq!(pages, 3);
+        assert_eq!(calls.get(), 3);
+    }
+}
@@ -1,30 +1,103 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value, json};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use versions::Versioning;
 
 pub mod vendor;
 
+/// Version of the serialized fields of `JvmData`, bump it when a field is added, removed or renamed
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// File types of packages which need to be installed, as opposed to portable archives
+const INSTALLER_FILE_TYPES: [&str; 7] = ["apk", "deb", "dmg", "exe", "msi", "pkg", "rpm"];
+
+/// Properties derived from the stored ones, only exported if explicitly included
+const DERIVED_PROPS: [&str; 1] = ["package_kind"];
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct JvmData {
     pub architecture: String,
+    /// Build number of the version e.g. 10 of 17.0.6+10
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
     pub checksum: Option<String>,
     pub checksum_url: Option<String>,
+    #[serde(default)]
+    pub checksums: Vec<String>,
     #[serde(serialize_with = "empty_vec_if_none")]
     pub features: Option<Vec<String>>,
     pub file_type: String,
     pub filename: String,
     pub image_type: String,
     pub java_version: String,
-    pub jvm_impl: String,
+    pub jvm_impl: JvmImpl,
     pub os: String,
     pub release_type: String,
-    pub size: Option<i32>,
+    /// Final URL `url` redirects to if redirects were resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_url: Option<String>,
+    pub size: Option<i64>,
     pub url: String,
     pub vendor: String,
     pub version: String,
 }
 
+/// Implementation of a JVM
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JvmImpl {
+    Graalvm,
+    #[default]
+    Hotspot,
+    Openj9,
+    /// Azul Zing/Platform Prime
+    Zing,
+}
+
+impl FromStr for JvmImpl {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "graalvm" => Ok(JvmImpl::Graalvm),
+            "hotspot" => Ok(JvmImpl::Hotspot),
+            "openj9" => Ok(JvmImpl::Openj9),
+            "zing" => Ok(JvmImpl::Zing),
+            _ => Err(eyre::eyre!("invalid jvm_impl: {s}")),
+        }
+    }
+}
+
+impl Display for JvmImpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            JvmImpl::Graalvm => "graalvm",
+            JvmImpl::Hotspot => "hotspot",
+            JvmImpl::Openj9 => "openj9",
+            JvmImpl::Zing => "zing",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Compares two versions semantically and by their build numbers if only those differ, falling back to comparing them
+/// as strings e.g. 17.0.6+9 < 17.0.6+10 < 17.0.7+1
+///
+/// Build metadata does not affect the precedence of semantic versions, so without the build numbers all builds of a
+/// version would be considered equal.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let build = |version: &str| vendor::parse_build(version).and_then(|build| build.parse::<u64>().ok());
+    match (Versioning::new(a), Versioning::new(b)) {
+        (Some(va), Some(vb)) => va.cmp(&vb).then_with(|| build(a).cmp(&build(b))),
+        _ => a.cmp(b),
+    }
+    .then_with(|| a.cmp(b))
+}
+
 fn empty_vec_if_none<S>(x: &Option<Vec<String>>, s: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -64,19 +137,122 @@ impl JvmData {
         true
     }
 
+    /// Maps the item to its properties, derived properties like `package_kind` are only mapped if included
     pub fn map(item: &JvmData, include: &[String], exclude: &[String]) -> Map<String, Value> {
-        let props: HashMap<String, Value> = serde_json::from_value(serde_json::to_value(item).unwrap()).unwrap();
+        // prefer the strongest of all known checksums
+        let mut item = item.clone();
+        item.prefer_strongest_checksum();
+        let props = JvmData::props(&item);
         let mut map = Map::new();
         for prop in &props {
-            if (include.is_empty() || include.contains(prop.0)) && !exclude.contains(prop.0) {
+            let included = match DERIVED_PROPS.contains(&prop.0.as_str()) {
+                true => include.contains(prop.0),
+                false => include.is_empty() || include.contains(prop.0),
+            };
+            if included && !exclude.contains(prop.0) {
                 map.insert(prop.0.clone(), json!(prop.1.clone()));
             }
         }
         map
     }
 
+    /// Maps the item to the given properties in the given order, unknown properties are skipped
+    pub fn map_fields(item: &JvmData, fields: &[String]) -> Map<String, Value> {
+        let mut props = JvmData::map(item, fields, &[]);
+        fields
+            .iter()
+            .filter_map(|field| props.remove(field).map(|value| (field.clone(), value)))
+            .collect()
+    }
+
+    /// Renames the properties of a mapped item in place, properties without a new name are kept
+    pub fn rename(map: Map<String, Value>, renames: &BTreeMap<String, String>) -> Map<String, Value> {
+        if renames.is_empty() {
+            return map;
+        }
+        map.into_iter()
+            .map(|(key, value)| match renames.get(&key) {
+                Some(name) => (name.clone(), value),
+                None => (key, value),
+            })
+            .collect()
+    }
+
+    /// Replaces the `features` array of a mapped item by its values joined with commas e.g. for flat consumers
+    pub fn compact_features(mut map: Map<String, Value>) -> Map<String, Value> {
+        if let Some(Value::Array(features)) = map.get("features") {
+            let joined = features.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(",");
+            map.insert("features".to_string(), Value::String(joined));
+        }
+        map
+    }
+
+    /// Returns `installer` for packages which need to be installed e.g. macOS `dmg` and `pkg`, `archive` otherwise
+    pub fn package_kind(&self) -> &'static str {
+        match INSTALLER_FILE_TYPES.contains(&self.file_type.as_str()) {
+            true => "installer",
+            false => "archive",
+        }
+    }
+
+    /// Returns the names of all properties a record can be mapped to including the derived ones
+    pub fn property_names() -> Vec<String> {
+        // optional properties which are skipped if empty are set to list them as well
+        let item = JvmData {
+            build: Some(String::new()),
+            resolved_url: Some(String::new()),
+            ..Default::default()
+        };
+        JvmData::props(&item).into_keys().collect()
+    }
+
+    /// Returns the stored properties and the derived `package_kind`, sorted to keep a stable order
+    fn props(item: &JvmData) -> BTreeMap<String, Value> {
+        let mut props: BTreeMap<String, Value> = serde_json::from_value(serde_json::to_value(item).unwrap()).unwrap();
+        props.insert("package_kind".to_string(), json!(item.package_kind()));
+        props
+    }
+
+    /// Sets the build number from the version e.g. 10 of 17.0.6+10, none if it has none
+    pub fn derive_build(&mut self) {
+        self.build = vendor::parse_build(&self.version);
+    }
+
+    /// Makes the checksum with the strongest algorithm the primary one, a weaker primary is kept in `checksums`
+    pub fn prefer_strongest_checksum(&mut self) {
+        let strongest = self.get_checksum();
+        if strongest == self.checksum {
+            return;
+        }
+        if let Some(primary) = self.checksum.take()
+            && !self.checksums.contains(&primary)
+        {
+            self.checksums.push(primary);
+        }
+        self.checksum = strongest;
+    }
+
+    /// Replaces the checksums with the algorithm of a refreshed one, the strongest checksum stays the primary one
+    pub fn replace_checksum(&mut self, checksum: String) {
+        let algorithm = |checksum: &str| checksum.split_once(':').map(|(algorithm, _)| algorithm.to_string());
+        let refreshed = algorithm(&checksum);
+        self.checksums.extend(self.checksum.take());
+        self.checksums.retain(|other| algorithm(other) != refreshed);
+        self.checksum = Some(checksum);
+        self.prefer_strongest_checksum();
+    }
+
+    /// Returns the checksum with the strongest algorithm of `checksum` and `checksums`
+    pub fn get_checksum(&self) -> Option<String> {
+        self.checksums
+            .iter()
+            .chain(self.checksum.iter())
+            .max_by_key(|checksum| checksum_strength(checksum))
+            .cloned()
+    }
+
     fn matches(item: &JvmData, key: &str, values: &[String]) -> bool {
-        let props: HashMap<String, Value> = serde_json::from_value(serde_json::to_value(item).unwrap()).unwrap();
+        let props = JvmData::props(item);
         let contains = |arr: &Vec<String>, v: &String| !arr.is_empty() && arr.contains(v);
         let eq = values
             .iter()
@@ -128,25 +304,113 @@ impl JvmData {
             true
         }
     }
+
+    /// Returns a record of the required fields, fails naming the fields which are empty
+    ///
+    /// `build` is derived from the version, the checksums and `resolved_url` are left empty as they are set by the
+    /// vendor if known or derived after fetching.
+    pub fn new(fields: RequiredFields) -> eyre::Result<JvmData> {
+        let mut data = JvmData {
+            architecture: fields.architecture,
+            features: fields.features,
+            file_type: fields.file_type,
+            filename: fields.filename,
+            image_type: fields.image_type,
+            java_version: fields.java_version,
+            jvm_impl: fields.jvm_impl,
+            os: fields.os,
+            release_type: fields.release_type,
+            size: fields.size,
+            url: fields.url,
+            vendor: fields.vendor,
+            version: fields.version,
+            ..Default::default()
+        };
+        let empty = [
+            ("architecture", &data.architecture),
+            ("file_type", &data.file_type),
+            ("filename", &data.filename),
+            ("image_type", &data.image_type),
+            ("java_version", &data.java_version),
+            ("os", &data.os),
+            ("release_type", &data.release_type),
+            ("url", &data.url),
+            ("vendor", &data.vendor),
+            ("version", &data.version),
+        ]
+        .into_iter()
+        .filter(|(_, value)| value.is_empty())
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+        if !empty.is_empty() {
+            return Err(eyre::eyre!(
+                "empty required fields of {}: {}",
+                match data.url.is_empty() {
+                    true => "record",
+                    false => &data.url,
+                },
+                empty.join(", ")
+            ));
+        }
+        data.derive_build();
+        Ok(data)
+    }
+}
+
+/// Fields every record needs for `JvmData::new`
+///
+/// Unlike `JvmData { .. }` with `..Default::default()`, a forgotten field fails to compile as there is no default.
+/// `features` and `size` may be none but have to be given explicitly.
+#[derive(Debug)]
+pub struct RequiredFields {
+    pub architecture: String,
+    pub features: Option<Vec<String>>,
+    pub file_type: String,
+    pub filename: String,
+    pub image_type: String,
+    pub java_version: String,
+    pub jvm_impl: JvmImpl,
+    pub os: String,
+    pub release_type: String,
+    pub size: Option<i64>,
+    pub url: String,
+    pub vendor: String,
+    pub version: String,
+}
+
+/// Returns the relative strength of a checksum based on its algorithm prefix e.g. `sha256:`
+fn checksum_strength(checksum: &str) -> u8 {
+    match checksum.split_once(':').map(|(algorithm, _)| algorithm) {
+        Some("sha512") => 4,
+        Some("sha256") => 3,
+        Some("sha1") => 2,
+        Some("md5") => 1,
+        _ => 0,
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
 
     fn get_jvmdata() -> JvmData {
         JvmData {
             architecture: "x86_64".to_string(),
+            build: None,
             checksum: Some("sha256:checksum".to_string()),
             checksum_url: Some("http://example.com/checksum".to_string()),
+            checksums: vec!["sha256:checksum".to_string()],
             features: Some(vec!["feature1".to_string(), "feature2".to_string()]),
             file_type: "tar.gz".to_string(),
             filename: "openjdk.tar.gz".to_string(),
             image_type: "jdk".to_string(),
             java_version: "11".to_string(),
-            jvm_impl: "hotspot".to_string(),
+            jvm_impl: JvmImpl::Hotspot,
             os: "linux".to_string(),
             release_type: "ga".to_string(),
+            resolved_url: None,
             size: Some(12345678),
             url: "http://example.com/download".to_string(),
             vendor: "AdoptOpenJDK".to_string(),
@@ -219,6 +483,7 @@ mod tests {
             "architecture".to_string(),
             "checksum".to_string(),
             "checksum_url".to_string(),
+            "checksums".to_string(),
             "features".to_string(),
             "file_type".to_string(),
             "filename".to_string(),
@@ -226,6 +491,7 @@ mod tests {
             "java_version".to_string(),
             "jvm_impl".to_string(),
             "os".to_string(),
+            "package_kind".to_string(),
             "release_type".to_string(),
             "size".to_string(),
             "url".to_string(),
@@ -238,6 +504,7 @@ mod tests {
         assert_eq!(map.get("architecture").unwrap(), "x86_64");
         assert_eq!(map.get("checksum").unwrap(), "sha256:checksum");
         assert_eq!(map.get("checksum_url").unwrap(), "http://example.com/checksum");
+        assert_eq!(map.get("checksums").unwrap(), &json!(vec!["sha256:checksum"]));
         assert_eq!(map.get("features").unwrap(), &json!(vec!["feature1", "feature2"]));
         assert_eq!(map.get("file_type").unwrap(), "tar.gz");
         assert_eq!(map.get("filename").unwrap(), "openjdk.tar.gz");
@@ -245,6 +512,7 @@ mod tests {
         assert_eq!(map.get("java_version").unwrap(), "11");
         assert_eq!(map.get("jvm_impl").unwrap(), "hotspot");
         assert_eq!(map.get("os").unwrap(), "linux");
+        assert_eq!(map.get("package_kind").unwrap(), "archive");
         assert_eq!(map.get("release_type").unwrap(), "ga");
         assert_eq!(map.get("size").unwrap(), 12345678);
         assert_eq!(map.get("url").unwrap(), "http://example.com/download");
@@ -252,6 +520,20 @@ mod tests {
         assert_eq!(map.get("version").unwrap(), "11.0.2");
     }
 
+    #[test]
+    fn test_map_fields() {
+        let jvm_data = get_jvmdata();
+        let fields = ["version", "url", "os", "unknown", "checksum"].map(String::from);
+
+        let map = JvmData::map_fields(&jvm_data, &fields);
+
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["version", "url", "os", "checksum"]);
+        assert_eq!(
+            serde_json::to_string(&map).unwrap(),
+            r#"{"version":"11.0.2","url":"http://example.com/download","os":"linux","checksum":"sha256:checksum"}"#
+        );
+    }
+
     #[test]
     fn test_map_with_include() {
         let jvm_data = get_jvmdata();
@@ -311,4 +593,343 @@ mod tests {
         assert_eq!(map.get("vendor").unwrap(), "AdoptOpenJDK");
         assert_eq!(map.get("version").unwrap(), "11.0.2");
     }
+
+    #[test]
+    fn test_rename() {
+        let jvm_data = get_jvmdata();
+        let renames = BTreeMap::from([
+            ("java_version".to_string(), "jdk_version".to_string()),
+            ("size".to_string(), "bytes".to_string()),
+        ]);
+
+        let map = JvmData::rename(JvmData::map(&jvm_data, &[], &["size".to_string()]), &renames);
+        assert_eq!(map.get("jdk_version").unwrap(), "11");
+        assert!(!map.contains_key("java_version"));
+        // excluded properties are not renamed into the output
+        assert!(!map.contains_key("bytes"));
+        assert!(!map.contains_key("size"));
+        // the renamed property keeps its position
+        let keys = map.keys().collect::<Vec<_>>();
+        assert_eq!(
+            keys[keys.iter().position(|k| *k == "image_type").unwrap() + 1],
+            "jdk_version"
+        );
+
+        let fields = ["version", "java_version"].map(String::from);
+        let map = JvmData::rename(JvmData::map_fields(&jvm_data, &fields), &renames);
+        assert_eq!(
+            serde_json::to_string(&map).unwrap(),
+            r#"{"version":"11.0.2","jdk_version":"11"}"#
+        );
+    }
+
+    #[test]
+    fn test_compact_features() {
+        let jvm_data = get_jvmdata();
+        let map = JvmData::compact_features(JvmData::map(&jvm_data, &[], &[]));
+        assert_eq!(map["features"], "feature1,feature2");
+        // the position of features is kept
+        let keys = map.keys().collect::<Vec<_>>();
+        assert_eq!(
+            keys[keys.iter().position(|k| *k == "checksums").unwrap() + 1],
+            "features"
+        );
+
+        let jvm_data = JvmData {
+            features: None,
+            ..get_jvmdata()
+        };
+        let map = JvmData::compact_features(JvmData::map(&jvm_data, &[], &[]));
+        assert_eq!(map["features"], "");
+        let map = JvmData::compact_features(JvmData::map(&jvm_data, &[], &["features".to_string()]));
+        assert!(!map.contains_key("features"));
+    }
+
+    #[test]
+    fn test_map_with_multiple_checksums() {
+        let mut jvm_data = get_jvmdata();
+        jvm_data.checksum = Some("sha1:checksum1".to_string());
+        jvm_data.checksums = vec!["sha1:checksum1".to_string(), "sha256:checksum256".to_string()];
+
+        assert_eq!(jvm_data.get_checksum(), Some("sha256:checksum256".to_string()));
+
+        let map = JvmData::map(&jvm_data, &[], &[]);
+        assert_eq!(map.get("checksum").unwrap(), "sha256:checksum256");
+        assert_eq!(
+            map.get("checksums").unwrap(),
+            &json!(vec!["sha1:checksum1", "sha256:checksum256"])
+        );
+
+        // a weaker primary checksum is exported along with the others
+        jvm_data.checksum = Some("md5:checksum5".to_string());
+        let map = JvmData::map(&jvm_data, &[], &[]);
+        assert_eq!(map.get("checksum").unwrap(), "sha256:checksum256");
+        assert_eq!(
+            map.get("checksums").unwrap(),
+            &json!(vec!["sha1:checksum1", "sha256:checksum256", "md5:checksum5"])
+        );
+    }
+
+    #[test]
+    fn test_replace_checksum() {
+        let mut jvm_data = JvmData {
+            checksum: Some("sha256:old".to_string()),
+            checksums: vec!["sha1:checksum1".to_string(), "sha256:old".to_string()],
+            ..get_jvmdata()
+        };
+        jvm_data.replace_checksum("sha256:new".to_string());
+        assert_eq!(jvm_data.checksum.as_deref(), Some("sha256:new"));
+        assert_eq!(jvm_data.checksums, ["sha1:checksum1"]);
+
+        // a refreshed weaker checksum does not replace a stronger primary one
+        let mut jvm_data = JvmData {
+            checksum: Some("sha512:checksum512".to_string()),
+            checksums: vec!["sha1:old".to_string()],
+            ..get_jvmdata()
+        };
+        jvm_data.replace_checksum("sha1:new".to_string());
+        assert_eq!(jvm_data.checksum.as_deref(), Some("sha512:checksum512"));
+        assert_eq!(jvm_data.checksums, ["sha512:checksum512", "sha1:new"]);
+    }
+
+    #[test]
+    fn test_get_checksum() {
+        let mut jvm_data = get_jvmdata();
+        jvm_data.checksums = vec![];
+        assert_eq!(jvm_data.get_checksum(), Some("sha256:checksum".to_string()));
+
+        jvm_data.checksum = None;
+        assert_eq!(jvm_data.get_checksum(), None);
+
+        jvm_data.checksums = vec![
+            "md5:checksum".to_string(),
+            "sha512:checksum".to_string(),
+            "sha1:checksum".to_string(),
+        ];
+        assert_eq!(jvm_data.get_checksum(), Some("sha512:checksum".to_string()));
+    }
+
+    #[test]
+    fn test_dedupe_by_url() {
+        let jvm_data = get_jvmdata();
+        let reordered = JvmData {
+            features: Some(vec!["feature2".to_string(), "feature1".to_string()]),
+            size: None,
+            ..get_jvmdata()
+        };
+        let other = JvmData {
+            url: "http://example.com/other".to_string(),
+            ..get_jvmdata()
+        };
+        let set = HashSet::from([jvm_data, reordered, other]);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_macos_installers_and_archives() {
+        let record = |file_type: &str| JvmData {
+            file_type: file_type.to_string(),
+            filename: format!("microsoft-jdk-21.0.6-macos-aarch64.{file_type}"),
+            os: "macosx".to_string(),
+            url: format!("https://aka.ms/download-jdk/microsoft-jdk-21.0.6-macos-aarch64.{file_type}"),
+            vendor: "microsoft".to_string(),
+            ..get_jvmdata()
+        };
+        let jvm_data = ["pkg", "tar.gz"].map(record).into_iter().collect::<HashSet<_>>();
+        assert_eq!(jvm_data.len(), 2);
+
+        for (package_kind, file_type) in [("installer", "pkg"), ("archive", "tar.gz")] {
+            let filters = HashMap::from([("package_kind".to_string(), vec![package_kind.to_string()])]);
+            let matching = jvm_data
+                .iter()
+                .filter(|item| JvmData::filter(item, &filters))
+                .collect::<Vec<_>>();
+            assert_eq!(matching.len(), 1);
+            assert_eq!(matching[0].file_type, file_type);
+            // the derived property is only exported on request
+            assert!(!JvmData::map(matching[0], &[], &[]).contains_key("package_kind"));
+            let include = ["package_kind".to_string()];
+            assert_eq!(JvmData::map(matching[0], &include, &[])["package_kind"], package_kind);
+            assert_eq!(JvmData::map_fields(matching[0], &include)["package_kind"], package_kind);
+        }
+        assert_eq!(record("dmg").package_kind(), "installer");
+        assert_eq!(record("zip").package_kind(), "archive");
+    }
+
+    #[test]
+    fn test_map_large_size() {
+        let jvm_data = JvmData {
+            size: Some(3_000_000_000),
+            ..get_jvmdata()
+        };
+        let map = JvmData::map(&jvm_data, &["size".to_string()], &[]);
+        assert_eq!(map.get("size").unwrap(), &json!(3_000_000_000u64));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut jvm_data = get_jvmdata();
+        jvm_data.resolved_url = Some("http://example.com/redirect".to_string());
+        jvm_data.build = Some("9".to_string());
+
+        // changing these fields breaks consumers, bump SCHEMA_VERSION along with them
+        assert_eq!(SCHEMA_VERSION, 2);
+        let json = serde_json::to_value(&jvm_data).unwrap();
+        let fields = json.as_object().unwrap().keys().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                "architecture",
+                "build",
+                "checksum",
+                "checksum_url",
+                "checksums",
+                "features",
+                "file_type",
+                "filename",
+                "image_type",
+                "java_version",
+                "jvm_impl",
+                "os",
+                "release_type",
+                "resolved_url",
+                "size",
+                "url",
+                "vendor",
+                "version",
+            ]
+        );
+        assert_eq!(json["jvm_impl"], "hotspot");
+        assert_eq!(json["features"], json!(["feature1", "feature2"]));
+
+        let actual = serde_json::from_value::<JvmData>(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&actual).unwrap(),
+            serde_json::to_value(&jvm_data).unwrap()
+        );
+
+        // records without features, build and resolved_url keep their fields, only build and resolved_url are skipped
+        let json = serde_json::to_value(JvmData::default()).unwrap();
+        assert_eq!(json["features"], json!([]));
+        assert!(json.get("build").is_none());
+        assert!(json.get("resolved_url").is_none());
+    }
+
+    #[test]
+    fn test_deserialize_older_payload() {
+        // written before build, checksum_url, checksums, size and resolved_url were added
+        let json = r#"{
+            "architecture": "x86_64",
+            "checksum": "sha256:checksum",
+            "features": [],
+            "file_type": "tar.gz",
+            "filename": "openjdk.tar.gz",
+            "image_type": "jdk",
+            "java_version": "11",
+            "jvm_impl": "hotspot",
+            "os": "linux",
+            "release_type": "ga",
+            "url": "http://example.com/download",
+            "vendor": "AdoptOpenJDK",
+            "version": "11.0.2"
+        }"#;
+        let actual = serde_json::from_str::<JvmData>(json).unwrap();
+        // PartialEq only compares the url, compare all fields
+        let expected = JvmData {
+            checksum_url: None,
+            checksums: vec![],
+            features: Some(vec![]),
+            size: None,
+            ..get_jvmdata()
+        };
+        assert_eq!(
+            serde_json::to_value(&actual).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(actual.build, None);
+        assert_eq!(actual.resolved_url, None);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        for (a, b, expected) in [
+            ("17.0.6+9", "17.0.6+10", Ordering::Less),
+            ("17.0.6+10", "17.0.7+1", Ordering::Less),
+            ("11.0.2+9", "11.0.2", Ordering::Greater),
+            ("21.0.1+12", "21.0.1+12", Ordering::Equal),
+            ("21.0.10", "21.0.2", Ordering::Greater),
+        ] {
+            assert_eq!(compare_versions(a, b), expected, "{a} <=> {b}");
+            assert_eq!(compare_versions(b, a), expected.reverse(), "{b} <=> {a}");
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let fields = || RequiredFields {
+            architecture: "x86_64".to_string(),
+            features: None,
+            file_type: "tar.gz".to_string(),
+            filename: "openjdk.tar.gz".to_string(),
+            image_type: "jdk".to_string(),
+            java_version: "11".to_string(),
+            jvm_impl: JvmImpl::Hotspot,
+            os: "linux".to_string(),
+            release_type: "ga".to_string(),
+            size: Some(12345678),
+            url: "http://example.com/download".to_string(),
+            vendor: "AdoptOpenJDK".to_string(),
+            version: "11.0.2".to_string(),
+        };
+        let actual = JvmData::new(fields()).unwrap();
+        assert_eq!(actual.architecture, "x86_64");
+        assert_eq!(actual.checksum, None);
+        assert_eq!(actual.features, None);
+        assert_eq!(actual.size, Some(12345678));
+        assert_eq!(actual.url, "http://example.com/download");
+        assert_eq!(actual.version, "11.0.2");
+        assert_eq!(actual.build, None);
+        let actual = JvmData::new(RequiredFields {
+            version: "11.0.2+9".to_string(),
+            ..fields()
+        })
+        .unwrap();
+        assert_eq!(actual.build.as_deref(), Some("9"));
+
+        let err = JvmData::new(RequiredFields {
+            os: String::new(),
+            version: String::new(),
+            ..fields()
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "empty required fields of http://example.com/download: os, version"
+        );
+        let err = JvmData::new(RequiredFields {
+            architecture: String::new(),
+            url: String::new(),
+            ..fields()
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "empty required fields of record: architecture, url");
+    }
+
+    #[test]
+    fn test_jvm_impl_from_str() {
+        for (actual, expected) in [
+            ("graalvm", JvmImpl::Graalvm),
+            ("hotspot", JvmImpl::Hotspot),
+            ("HotSpot", JvmImpl::Hotspot),
+            ("openj9", JvmImpl::Openj9),
+            ("zing", JvmImpl::Zing),
+        ] {
+            assert_eq!(actual.parse::<JvmImpl>().unwrap(), expected);
+            assert_eq!(expected.to_string(), actual.to_lowercase());
+        }
+
+        for invalid in ["", "hotpsot", "temurin"] {
+            assert!(invalid.parse::<JvmImpl>().is_err(), "Expected an error for: {invalid}");
+        }
+    }
 }
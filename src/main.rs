@@ -12,6 +12,8 @@ mod env;
 mod github;
 mod http;
 mod jvm;
+#[cfg(test)]
+mod test_util;
 
 fn main() -> eyre::Result<()> {
     env_logger::builder()